@@ -0,0 +1,191 @@
+// -----------------------------
+// RESULT COMPRESSION
+// -----------------------------
+// Result files can be stored gzipped (`<result>.gz`). Compression runs
+// through the system `gzip`; readers detect it from the magic bytes, so
+// callers never need to know how a result was stored.
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+pub fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC)
+}
+
+pub async fn is_gzip_file(path: &Path) -> std::io::Result<bool> {
+    let mut magic = [0u8; 2];
+    let mut file = fs::File::open(path).await?;
+    let mut read = 0;
+    while read < magic.len() {
+        let n = file.read(&mut magic[read..]).await?;
+        if n == 0 {
+            return Ok(false);
+        }
+        read += n;
+    }
+    Ok(is_gzip(&magic))
+}
+
+/// `result.xml` -> `result.xml.gz`
+pub fn gzip_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// Replaces `path` with `path.gz` and returns the new path.
+pub async fn compress_file(path: &Path) -> Result<PathBuf, String> {
+    let output = Command::new("gzip")
+        .arg("-f")
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Cannot run gzip: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "gzip failed for {:?}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(gzip_path(path))
+}
+
+/// Reads a result file, decompressing it first if it is gzipped.
+pub async fn read_result(path: &Path) -> Result<Vec<u8>, String> {
+    if !is_gzip_file(path).await.map_err(|e| format!("Cannot read {:?}: {}", path, e))? {
+        return fs::read(path).await.map_err(|e| format!("Cannot read {:?}: {}", path, e));
+    }
+
+    let output = Command::new("gzip")
+        .arg("-dc")
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Cannot run gzip: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Cannot decompress {:?}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// Copies a result to `destination`, gzipped if `compress` and plain
+/// otherwise, converting on the fly through `gzip` as needed. Only
+/// `destination` is written.
+pub async fn copy_result(source: &Path, destination: &Path, compress: bool) -> Result<(), String> {
+    let stored_compressed = is_gzip_file(source).await.map_err(|e| format!("Cannot read {:?}: {}", source, e))?;
+    if stored_compressed == compress {
+        return fs::copy(source, destination)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Cannot copy {:?} to {:?}: {}", source, destination, e));
+    }
+
+    let file = std::fs::File::create(destination).map_err(|e| format!("Cannot create {:?}: {}", destination, e))?;
+    let output = Command::new("gzip")
+        .arg(if compress { "-c" } else { "-dc" })
+        .arg(source)
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(file))
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Cannot run gzip: {}", e))?
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("Cannot run gzip: {}", e))?;
+
+    if !output.status.success() {
+        let _ = fs::remove_file(destination).await;
+        return Err(format!(
+            "gzip failed for {:?}: {}",
+            source,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn copy_result_converts_without_touching_other_files() {
+        let dir = std::env::temp_dir().join(format!("nucloflo_copy_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let plain = dir.join("result.xml");
+        std::fs::write(&plain, "<BlastOutput/>\n").unwrap();
+        // A user's file next to the destination must survive the save
+        let sibling = dir.join("saved.xml");
+        std::fs::write(&sibling, "keep me").unwrap();
+
+        let gzipped = dir.join("saved.xml.gz");
+        copy_result(&plain, &gzipped, true).await.unwrap();
+        let restored = dir.join("restored.xml");
+        copy_result(&gzipped, &restored, false).await.unwrap();
+
+        let contents = (
+            is_gzip_file(&gzipped).await.unwrap(),
+            std::fs::read_to_string(&restored).unwrap(),
+            std::fs::read_to_string(&sibling).unwrap(),
+            std::fs::read_to_string(&plain).unwrap(),
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(contents, (true, "<BlastOutput/>\n".to_string(), "keep me".to_string(), "<BlastOutput/>\n".to_string()));
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nucloflo_gzip_{}_{}", std::process::id(), name));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn compressed_results_parse_like_plain_ones() {
+        let dir = temp_dir("roundtrip");
+        let plain = dir.join("result.xml");
+        std::fs::write(&plain, crate::testing::CANNED_BLAST_XML).unwrap();
+
+        let gzipped = compress_file(&plain).await.unwrap();
+        let outcome = (
+            gzipped == dir.join("result.xml.gz"),
+            plain.exists(),
+            is_gzip_file(&gzipped).await.unwrap(),
+            read_result(&gzipped).await.unwrap(),
+            crate::results::load_report(&gzipped).await.map(|report| report.queries[0].hits[0].accession.clone()),
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (renamed, plain_left, magic, contents, accession) = outcome;
+        assert!(renamed && !plain_left && magic);
+        assert_eq!(contents, crate::testing::CANNED_BLAST_XML.as_bytes());
+        assert_eq!(accession.unwrap(), "MOCK0001");
+    }
+
+    #[tokio::test]
+    async fn streamed_output_can_be_gzipped() {
+        let dir = temp_dir("stream");
+        let path = dir.join("result.xml.gz");
+        let xml = crate::testing::CANNED_BLAST_XML.as_bytes();
+
+        let event = crate::streaming::stream_to_file(xml, &path, 1, true).await.unwrap();
+        let outcome = (is_gzip_file(&path).await.unwrap(), read_result(&path).await.unwrap());
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Progress counts what the engine produced, not the compressed size
+        assert_eq!(event.bytes, xml.len() as u64);
+        assert_eq!(outcome, (true, xml.to_vec()));
+    }
+}
@@ -0,0 +1,233 @@
+// -----------------------------
+// CREDENTIALS
+// -----------------------------
+// Engine configs refer to secrets by name; this module resolves the name to
+// a value from the environment, the OS keychain, or an encrypted file.
+//
+// Credentials file: application_root/credentials.json
+//   { "ncbi":  { "env": "NCBI_API_KEY" },
+//     "s3":    { "keychain": { "service": "nucloflo", "account": "s3" } },
+//     "ssh":   { "file": "ssh_password" } }
+//
+// "file" entries are keys of a JSON object stored in secrets.enc, encrypted with
+//   openssl enc -aes-256-cbc -pbkdf2 -in secrets.json -out secrets.enc
+// and unlocked with the passphrase in NUCLOFLO_SECRETS_PASSPHRASE.
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use tokio::process::Command;
+
+const SECRETS_PASSPHRASE_VAR: &str = "NUCLOFLO_SECRETS_PASSPHRASE";
+
+// Every secret value handed out, so text bound for disk can be scrubbed
+static KNOWN_SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// A credential value. Debug and Display never show it; use `expose` only
+/// where the value is handed to the service that needs it.
+#[derive(Clone)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        if !value.is_empty() {
+            let mut known = KNOWN_SECRETS.lock().expect("secret list lock poisoned");
+            if !known.contains(&value) {
+                known.push(value.clone());
+            }
+        }
+        Secret(value)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(****)")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("****")
+    }
+}
+
+/// Replaces any secret resolved so far with `****`.
+pub fn redact(text: &str) -> String {
+    let known = KNOWN_SECRETS.lock().expect("secret list lock poisoned");
+    known.iter().fold(text.to_string(), |text, secret| text.replace(secret.as_str(), "****"))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialSource {
+    Env(String),
+    Keychain { service: String, account: String },
+    File(String),
+}
+
+#[derive(Debug)]
+pub enum CredentialError {
+    UnknownCredential(String),
+    NotFound(String),
+    Unsupported(String),
+    Backend(String),
+}
+
+#[derive(Debug, Default)]
+pub struct CredentialStore {
+    sources: HashMap<String, CredentialSource>,
+    encrypted_file: PathBuf,
+}
+
+impl CredentialStore {
+    /// A missing credentials file means no named credentials are defined.
+    pub fn load(app_root: &Path) -> Result<Self, CredentialError> {
+        let path = app_root.join("credentials.json");
+        let sources = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| CredentialError::Backend(format!("Invalid {:?}: {}", path, e)))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(CredentialError::Backend(format!("Cannot read {:?}: {}", path, e))),
+        };
+        Ok(Self {
+            sources,
+            encrypted_file: app_root.join("secrets.enc"),
+        })
+    }
+
+    pub async fn resolve(&self, name: &str) -> Result<Secret, CredentialError> {
+        let source = self.sources.get(name)
+            .ok_or_else(|| CredentialError::UnknownCredential(name.to_string()))?;
+
+        let value = match source {
+            CredentialSource::Env(var) => std::env::var(var)
+                .map_err(|_| CredentialError::NotFound(format!("{} (env {})", name, var)))?,
+            CredentialSource::Keychain { service, account } => read_keychain(service, account).await?,
+            CredentialSource::File(key) => self.read_encrypted(key).await?,
+        };
+
+        if value.is_empty() {
+            return Err(CredentialError::NotFound(name.to_string()));
+        }
+        Ok(Secret::new(value))
+    }
+
+    async fn read_encrypted(&self, key: &str) -> Result<String, CredentialError> {
+        if !self.encrypted_file.exists() {
+            return Err(CredentialError::NotFound(format!("{:?}", self.encrypted_file)));
+        }
+        if std::env::var(SECRETS_PASSPHRASE_VAR).is_err() {
+            return Err(CredentialError::NotFound(format!("passphrase (env {})", SECRETS_PASSPHRASE_VAR)));
+        }
+
+        // The passphrase is read by openssl from the environment, never argv
+        let plaintext = run_secret_command(
+            Command::new("openssl")
+                .args(["enc", "-d", "-aes-256-cbc", "-pbkdf2"])
+                .arg("-pass").arg(format!("env:{}", SECRETS_PASSPHRASE_VAR))
+                .arg("-in").arg(&self.encrypted_file),
+        ).await?;
+
+        let secrets: HashMap<String, String> = serde_json::from_str(&plaintext)
+            .map_err(|_| CredentialError::Backend("Decrypted secrets file is not a JSON object".to_string()))?;
+        secrets.get(key)
+            .cloned()
+            .ok_or_else(|| CredentialError::NotFound(format!("{} in {:?}", key, self.encrypted_file)))
+    }
+}
+
+async fn read_keychain(service: &str, account: &str) -> Result<String, CredentialError> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        command.args(["find-generic-password", "-s", service, "-a", account, "-w"]);
+        command
+    } else if cfg!(target_os = "linux") {
+        let mut command = Command::new("secret-tool");
+        command.args(["lookup", "service", service, "account", account]);
+        command
+    } else {
+        return Err(CredentialError::Unsupported("OS keychain on this platform".to_string()));
+    };
+    run_secret_command(&mut command).await
+}
+
+// Runs a backend command whose stdout is the secret. stderr is dropped from
+// errors because some backends echo what they were asked to decrypt.
+async fn run_secret_command(command: &mut Command) -> Result<String, CredentialError> {
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| CredentialError::Backend(format!("Cannot run credential backend: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(CredentialError::Backend(format!(
+            "Credential backend exited with {}",
+            output.status
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A credentials.json in a fresh directory, removed on drop
+    struct TempRoot(PathBuf);
+
+    impl TempRoot {
+        fn new(name: &str, credentials_json: &str) -> Self {
+            let root = std::env::temp_dir().join(format!("nucloflo_credentials_{}_{}", std::process::id(), name));
+            std::fs::create_dir_all(&root).unwrap();
+            std::fs::write(root.join("credentials.json"), credentials_json).unwrap();
+            TempRoot(root)
+        }
+    }
+
+    impl Drop for TempRoot {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn resolves_env_credentials_and_masks_them() {
+        // Variable names are unique to this test, so parallel tests don't race
+        std::env::set_var("NUCLOFLO_TEST_API_KEY", "k3y-from-env");
+        let root = TempRoot::new("env", r#"{ "ncbi": { "env": "NUCLOFLO_TEST_API_KEY" } }"#);
+
+        let secret = CredentialStore::load(&root.0).unwrap().resolve("ncbi").await.unwrap();
+        assert_eq!(secret.expose(), "k3y-from-env");
+        assert!(!format!("{:?}", secret).contains("k3y"));
+        assert_eq!(secret.to_string(), "****");
+        assert_eq!(redact("GET /efetch?api_key=k3y-from-env&id=1"), "GET /efetch?api_key=****&id=1");
+    }
+
+    #[tokio::test]
+    async fn unknown_and_empty_credentials_are_errors() {
+        std::env::set_var("NUCLOFLO_TEST_EMPTY_KEY", "");
+        let root = TempRoot::new("errors", r#"{
+            "empty": { "env": "NUCLOFLO_TEST_EMPTY_KEY" },
+            "unset": { "env": "NUCLOFLO_TEST_UNSET_KEY" } }"#);
+        let store = CredentialStore::load(&root.0).unwrap();
+
+        assert!(matches!(store.resolve("missing").await, Err(CredentialError::UnknownCredential(name)) if name == "missing"));
+        assert!(matches!(store.resolve("empty").await, Err(CredentialError::NotFound(_))));
+        assert!(matches!(store.resolve("unset").await, Err(CredentialError::NotFound(_))));
+    }
+
+    #[test]
+    fn missing_credentials_file_defines_nothing() {
+        let root = std::env::temp_dir().join(format!("nucloflo_credentials_{}_none", std::process::id()));
+        let store = CredentialStore::load(&root).unwrap();
+        assert!(store.sources.is_empty());
+    }
+}
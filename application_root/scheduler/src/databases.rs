@@ -0,0 +1,97 @@
+// -----------------------------
+// DATABASE REGISTRY
+// -----------------------------
+// Maps database names used by jobs ("pfam", "swissprot", ...) onto local
+// files. Names that are not registered are passed through unchanged, so
+// remote databases like "nt" and plain paths keep working.
+//
+// Registry file: application_root/databases.json
+//   [{ "name": "pfam", "path": "/data/Pfam-A.hmm", "kind": "profile" }]
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::BlastEngineError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseKind {
+    Nucleotide,
+    Protein,
+    /// HMM profile library (e.g. Pfam) searched by hmmscan
+    Profile,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DatabaseEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub kind: DatabaseKind,
+}
+
+#[derive(Debug, Default)]
+pub struct DatabaseRegistry {
+    entries: Vec<DatabaseEntry>,
+}
+
+impl DatabaseRegistry {
+    /// A missing registry file simply means nothing is registered.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Cannot read {:?}: {}", path, e)),
+        };
+        let entries = serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid database registry {:?}: {}", path, e))?;
+        Ok(Self { entries })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DatabaseEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Local path for a registered name, or the name itself otherwise.
+    pub fn resolve(&self, name: &str) -> String {
+        self.get(name)
+            .map(|entry| entry.path.to_string_lossy().to_string())
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    pub fn kind_of(&self, name: &str) -> Option<DatabaseKind> {
+        self.get(name).map(|entry| entry.kind)
+    }
+}
+
+/// hmmscan needs the binary index files written by `hmmpress`
+/// (`.h3m/.h3i/.h3f/.h3p`); build them on first use.
+pub async fn ensure_profile_pressed(path: &Path) -> Result<(), BlastEngineError> {
+    if !path.exists() {
+        return Err(BlastEngineError::DatabaseUnavailable);
+    }
+
+    let pressed = ["h3m", "h3i", "h3f", "h3p"].iter().all(|ext| {
+        let mut index = path.as_os_str().to_os_string();
+        index.push(format!(".{}", ext));
+        Path::new(&index).exists()
+    });
+    if pressed {
+        return Ok(());
+    }
+
+    crate::log!("🔨 Pressing HMM profile database {:?}", path);
+    let output = Command::new("hmmpress")
+        .arg("-f")
+        .arg(path)
+        .output()
+        .await
+        .map_err(|e| BlastEngineError::ExecutionFailed(format!("Failed to run hmmpress: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(BlastEngineError::ExecutionFailed(format!(
+            "hmmpress failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    Ok(())
+}
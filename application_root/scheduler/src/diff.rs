@@ -0,0 +1,327 @@
+// -----------------------------
+// RUN COMPARISON
+// -----------------------------
+// Compares two result sets (e.g. the same queries against two database
+// versions) and reports which hits were gained, lost or changed per query.
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::results::{BlastReport, Hit, QueryResult};
+
+#[derive(Debug, Clone)]
+pub struct DiffThresholds {
+    /// Hits with a best e-value above this are ignored on both sides
+    pub max_evalue: f64,
+    /// Bitscore change above which a hit counts as "changed"
+    pub min_bitscore_delta: f64,
+    /// Percent-identity change above which a hit counts as "changed"
+    pub min_identity_delta: f64,
+}
+
+impl Default for DiffThresholds {
+    fn default() -> Self {
+        Self {
+            max_evalue: f64::INFINITY,
+            min_bitscore_delta: 1.0,
+            min_identity_delta: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HitChange {
+    Gained,
+    Lost,
+    Changed,
+}
+
+impl HitChange {
+    fn label(&self) -> &'static str {
+        match self {
+            HitChange::Gained => "gained",
+            HitChange::Lost => "lost",
+            HitChange::Changed => "changed",
+        }
+    }
+}
+
+/// Best-HSP summary of one hit on one side of the comparison.
+#[derive(Debug, Clone, Serialize)]
+pub struct HitSummary {
+    pub bitscore: f64,
+    pub evalue: f64,
+    pub identity: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HitDiff {
+    pub subject_id: String,
+    pub change: HitChange,
+    pub baseline: Option<HitSummary>,
+    pub candidate: Option<HitSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryDiff {
+    pub query: String,
+    pub unchanged: usize,
+    pub hits: Vec<HitDiff>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DiffSummary {
+    pub gained: usize,
+    pub lost: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunDiff {
+    pub summary: DiffSummary,
+    pub queries: Vec<QueryDiff>,
+}
+
+pub fn diff_reports(baseline: &BlastReport, candidate: &BlastReport, thresholds: &DiffThresholds) -> RunDiff {
+    let candidate_keys = query_keys(candidate);
+    let candidate_queries: HashMap<&QueryKey, &QueryResult> = candidate_keys.iter()
+        .zip(&candidate.queries)
+        .collect();
+
+    let mut summary = DiffSummary::default();
+    let mut queries = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (key, query) in query_keys(baseline).iter().zip(&baseline.queries) {
+        let other = candidate_queries.get(key).copied();
+        let query_diff = diff_query(&key.label(), Some(query), other, thresholds);
+        tally(&mut summary, &query_diff);
+        queries.push(query_diff);
+        seen.insert(key.clone());
+    }
+
+    // Queries that only appear in the candidate run contribute only gains
+    for (key, query) in candidate_keys.iter().zip(&candidate.queries) {
+        if seen.contains(key) {
+            continue;
+        }
+        let query_diff = diff_query(&key.label(), None, Some(query), thresholds);
+        tally(&mut summary, &query_diff);
+        queries.push(query_diff);
+    }
+
+    RunDiff { summary, queries }
+}
+
+// Query IDs are reassigned on every run (Query_1, Query_5021549, ...), so
+// queries are matched on their FASTA definition line, falling back to position.
+// Repeated deflines are told apart by how many times the defline came before.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct QueryKey {
+    name: String,
+    occurrence: usize,
+}
+
+impl QueryKey {
+    fn label(&self) -> String {
+        if self.occurrence == 0 {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, self.occurrence + 1)
+        }
+    }
+}
+
+fn query_keys(report: &BlastReport) -> Vec<QueryKey> {
+    let mut occurrences: HashMap<&str, usize> = HashMap::new();
+    report.queries.iter()
+        .enumerate()
+        .map(|(index, query)| {
+            if query.query_def.is_empty() || query.query_def == "No definition line" {
+                return QueryKey { name: format!("#{}", index + 1), occurrence: 0 };
+            }
+            let count = occurrences.entry(query.query_def.as_str()).or_default();
+            let key = QueryKey { name: query.query_def.clone(), occurrence: *count };
+            *count += 1;
+            key
+        })
+        .collect()
+}
+
+fn subject_key(hit: &Hit) -> &str {
+    if hit.accession.is_empty() { &hit.id } else { &hit.accession }
+}
+
+fn summarize(hit: &Hit) -> Option<HitSummary> {
+    let best = hit.hsps.iter().max_by(|a, b| a.bit_score.total_cmp(&b.bit_score))?;
+    Some(HitSummary {
+        bitscore: best.bit_score,
+        evalue: best.evalue,
+        identity: best.percent_identity(),
+    })
+}
+
+fn significant_hits<'a>(query: Option<&'a QueryResult>, thresholds: &DiffThresholds) -> Vec<(&'a str, HitSummary)> {
+    query.map(|q| q.hits.as_slice()).unwrap_or_default()
+        .iter()
+        .filter_map(|hit| summarize(hit).map(|summary| (subject_key(hit), summary)))
+        .filter(|(_, summary)| summary.evalue <= thresholds.max_evalue)
+        .collect()
+}
+
+fn diff_query(
+    key: &str,
+    baseline: Option<&QueryResult>,
+    candidate: Option<&QueryResult>,
+    thresholds: &DiffThresholds,
+) -> QueryDiff {
+    let before = significant_hits(baseline, thresholds);
+    let mut after: HashMap<&str, HitSummary> = significant_hits(candidate, thresholds).into_iter().collect();
+
+    let mut hits = Vec::new();
+    let mut unchanged = 0;
+
+    for (subject, old) in before {
+        match after.remove(subject) {
+            Some(new) => {
+                let changed = (new.bitscore - old.bitscore).abs() > thresholds.min_bitscore_delta
+                    || (new.identity - old.identity).abs() > thresholds.min_identity_delta;
+                if changed {
+                    hits.push(HitDiff {
+                        subject_id: subject.to_string(),
+                        change: HitChange::Changed,
+                        baseline: Some(old),
+                        candidate: Some(new),
+                    });
+                } else {
+                    unchanged += 1;
+                }
+            }
+            None => hits.push(HitDiff {
+                subject_id: subject.to_string(),
+                change: HitChange::Lost,
+                baseline: Some(old),
+                candidate: None,
+            }),
+        }
+    }
+
+    // Keep gained hits in the candidate's rank order
+    for (subject, _) in significant_hits(candidate, thresholds) {
+        if let Some(new) = after.remove(subject) {
+            hits.push(HitDiff {
+                subject_id: subject.to_string(),
+                change: HitChange::Gained,
+                baseline: None,
+                candidate: Some(new),
+            });
+        }
+    }
+
+    QueryDiff { query: key.to_string(), unchanged, hits }
+}
+
+fn tally(summary: &mut DiffSummary, query: &QueryDiff) {
+    summary.unchanged += query.unchanged;
+    for hit in &query.hits {
+        match hit.change {
+            HitChange::Gained => summary.gained += 1,
+            HitChange::Lost => summary.lost += 1,
+            HitChange::Changed => summary.changed += 1,
+        }
+    }
+}
+
+impl RunDiff {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("run diff is serializable")
+    }
+
+    pub fn to_table(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!(
+            "{:<30} {:<24} {:<8} {:>10} {:>10} {:>10} {:>10}\n",
+            "QUERY", "SUBJECT", "CHANGE", "BITS_OLD", "BITS_NEW", "IDENT_OLD", "IDENT_NEW"
+        ));
+
+        for query in &self.queries {
+            for hit in &query.hits {
+                let value = |side: &Option<HitSummary>, pick: fn(&HitSummary) -> f64| {
+                    side.as_ref().map(|s| format!("{:.2}", pick(s))).unwrap_or_else(|| "-".to_string())
+                };
+                out.push_str(&format!(
+                    "{:<30} {:<24} {:<8} {:>10} {:>10} {:>10} {:>10}\n",
+                    truncate(&query.query, 30),
+                    truncate(&hit.subject_id, 24),
+                    hit.change.label(),
+                    value(&hit.baseline, |s| s.bitscore),
+                    value(&hit.candidate, |s| s.bitscore),
+                    value(&hit.baseline, |s| s.identity),
+                    value(&hit.candidate, |s| s.identity),
+                ));
+            }
+        }
+
+        out.push_str(&format!(
+            "\n{} gained, {} lost, {} changed, {} unchanged\n",
+            self.summary.gained, self.summary.lost, self.summary.changed, self.summary.unchanged
+        ));
+        out
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(width - 1).collect();
+    short.push('~');
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::Hsp;
+
+    fn query(def: &str, subjects: &[(&str, f64)]) -> QueryResult {
+        QueryResult {
+            query_def: def.to_string(),
+            hits: subjects.iter().map(|(accession, bits)| Hit {
+                accession: accession.to_string(),
+                hsps: vec![Hsp { bit_score: *bits, align_len: 100, identity: 100, ..Hsp::default() }],
+                ..Hit::default()
+            }).collect(),
+            ..QueryResult::default()
+        }
+    }
+
+    fn report(queries: Vec<QueryResult>) -> BlastReport {
+        BlastReport { queries, ..BlastReport::default() }
+    }
+
+    #[test]
+    fn duplicate_deflines_are_matched_by_occurrence() {
+        let baseline = report(vec![query("dup", &[("A", 50.0)]), query("dup", &[("B", 60.0)])]);
+        let candidate = report(vec![query("dup", &[("A", 50.0)]), query("dup", &[("B", 60.0)])]);
+
+        let diff = diff_reports(&baseline, &candidate, &DiffThresholds::default());
+        assert_eq!(diff.summary.unchanged, 2);
+        assert_eq!(diff.summary.gained + diff.summary.lost + diff.summary.changed, 0);
+        let labels: Vec<&str> = diff.queries.iter().map(|q| q.query.as_str()).collect();
+        assert_eq!(labels, ["dup", "dup (2)"]);
+    }
+
+    #[test]
+    fn reports_gained_lost_and_changed_hits() {
+        let baseline = report(vec![query("q", &[("A", 50.0), ("B", 60.0)])]);
+        let candidate = report(vec![query("q", &[("B", 70.0), ("C", 40.0)]), query("new", &[("D", 30.0)])]);
+
+        let diff = diff_reports(&baseline, &candidate, &DiffThresholds::default());
+        assert_eq!(diff.summary.lost, 1);
+        assert_eq!(diff.summary.changed, 1);
+        assert_eq!(diff.summary.gained, 2);
+        assert_eq!(diff.queries[1].query, "new");
+    }
+}
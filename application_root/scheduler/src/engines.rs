@@ -0,0 +1,137 @@
+// -----------------------------
+// ENGINE REGISTRY
+// -----------------------------
+// Per-engine settings keyed by engine id ("python", "rust", "diamond",
+// "hmmer", and "ncbi" for efetch). Entries in the registry file override the
+// built-in defaults.
+//
+// Registry file: application_root/engines.json
+//   { "python": { "rate_limit": { "requests_per_sec": 0.1, "max_concurrent": 1,
+//                                 "cooldown_secs": 60, "max_retries": 3 } },
+//     "diamond": { "compress_output": true },
+//     "ncbi":   { "credentials": { "api_key": "ncbi" },
+//                 "rate_limit": { "requests_per_sec": 3, "max_concurrent": 1, "cooldown_secs": 10 } } }
+//
+// Credentials are names from credentials.json, never the secrets themselves.
+//
+// Rate limits hold across scheduler processes (the UI runs one per job):
+// request spacing and cool-downs are shared through
+// application_root/governors/<id>.state. max_concurrent only counts the jobs
+// of one process.
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitConfig {
+    pub requests_per_sec: f64,
+    pub max_concurrent: usize,
+    /// Pause before the next request after a 429/502/503/504 response
+    pub cooldown_secs: u64,
+    /// Times a throttled job is retried before it fails
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_max_retries() -> u32 { 3 }
+
+impl RateLimitConfig {
+    fn validate(&self) -> Result<(), String> {
+        // 0 means unspaced; otherwise the interval 1 / rate must fit a Duration
+        let rate = self.requests_per_sec;
+        let valid = rate == 0.0 || (rate > 0.0 && std::time::Duration::try_from_secs_f64(1.0 / rate).is_ok());
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("requests_per_sec must be 0 or a usable positive rate, got {}", rate))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EngineConfig {
+    /// Only remote engines need one; local engines run unthrottled
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Purpose ("api_key", "password", ...) -> credential name
+    #[serde(default)]
+    pub credentials: HashMap<String, String>,
+    /// Gzip results unless a job says otherwise
+    #[serde(default)]
+    pub compress_output: bool,
+}
+
+#[derive(Debug)]
+pub struct EngineRegistry {
+    engines: HashMap<String, EngineConfig>,
+}
+
+impl Default for EngineRegistry {
+    fn default() -> Self {
+        // The Python engine calls NCBI's BLAST URL API, which asks for no more
+        // than one request every 10 seconds
+        let python = EngineConfig {
+            rate_limit: Some(RateLimitConfig {
+                requests_per_sec: 0.1,
+                max_concurrent: 1,
+                cooldown_secs: 60,
+                max_retries: default_max_retries(),
+            }),
+            credentials: HashMap::new(),
+            compress_output: false,
+        };
+        Self { engines: HashMap::from([("python".to_string(), python)]) }
+    }
+}
+
+impl EngineRegistry {
+    /// A missing registry file means the defaults apply.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let mut registry = Self::default();
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(registry),
+            Err(e) => return Err(format!("Cannot read {:?}: {}", path, e)),
+        };
+        let entries: HashMap<String, EngineConfig> = serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid engine registry {:?}: {}", path, e))?;
+        for (id, config) in &entries {
+            if let Some(limits) = &config.rate_limit {
+                limits.validate().map_err(|e| format!("Invalid rate limit for {:?} in {:?}: {}", id, path, e))?;
+            }
+        }
+        registry.engines.extend(entries);
+        Ok(registry)
+    }
+
+    pub fn get(&self, id: &str) -> EngineConfig {
+        self.engines.get(id).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(requests_per_sec: f64) -> RateLimitConfig {
+        RateLimitConfig { requests_per_sec, max_concurrent: 1, cooldown_secs: 0, max_retries: 0 }
+    }
+
+    #[test]
+    fn rate_limits_must_give_a_usable_interval() {
+        assert!(rate(0.0).validate().is_ok());
+        assert!(rate(0.1).validate().is_ok());
+        for bad in [-1.0, f64::NAN, 1e-300] {
+            assert!(rate(bad).validate().is_err(), "{} accepted", bad);
+        }
+    }
+
+    #[test]
+    fn load_rejects_invalid_rate_limits() {
+        let path = std::env::temp_dir().join(format!("nucloflo_engines_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{ "python": { "rate_limit": { "requests_per_sec": 1e-300,
+            "max_concurrent": 1, "cooldown_secs": 0 } } }"#).unwrap();
+        let loaded = EngineRegistry::load(&path);
+        let _ = std::fs::remove_file(&path);
+        assert!(loaded.unwrap_err().contains("python"));
+    }
+}
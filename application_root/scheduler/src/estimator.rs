@@ -0,0 +1,236 @@
+// -----------------------------
+// RUNTIME ESTIMATOR
+// -----------------------------
+// Predicts how long a job will spend in its engine from past jobs with the
+// same program and database, scaled by query size. Each group fits
+//   seconds = overhead + per_residue * residues
+// over its most recent runs. Groups without history fall back to the
+// program alone, then to every past run; with no history there is no estimate.
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::history::HistoryEntry;
+
+/// Runs per group the fit is based on; older runs are ignored.
+const MAX_SAMPLES: usize = 100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuerySize {
+    pub sequences: u64,
+    pub residues: u64,
+}
+
+/// Counts sequences and residues in a FASTA file.
+pub async fn query_size(path: &Path) -> std::io::Result<QuerySize> {
+    let text = tokio::fs::read_to_string(path).await?;
+    let mut size = QuerySize::default();
+    for line in text.lines() {
+        if line.starts_with('>') {
+            size.sequences += 1;
+        } else {
+            size.residues += line.chars().filter(|c| !c.is_whitespace()).count() as u64;
+        }
+    }
+    // Bare sequence without a header
+    if size.sequences == 0 && size.residues > 0 {
+        size.sequences = 1;
+    }
+    Ok(size)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LinearFit {
+    overhead_secs: f64,
+    secs_per_residue: f64,
+    samples: usize,
+}
+
+impl LinearFit {
+    // Least squares on (residues, seconds); a single size or a negative
+    // slope degrades to a plain ratio or mean
+    fn from_samples(samples: &[(f64, f64)]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let n = samples.len() as f64;
+        let mean_x = samples.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = samples.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let var_x = samples.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+        let cov = samples.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>();
+
+        let (overhead_secs, secs_per_residue) = if var_x > 0.0 && cov >= 0.0 {
+            let slope = cov / var_x;
+            let overhead = mean_y - slope * mean_x;
+            if overhead >= 0.0 { (overhead, slope) } else { (0.0, mean_y / mean_x) }
+        } else if var_x > 0.0 {
+            (mean_y, 0.0)
+        } else if mean_x > 0.0 {
+            (0.0, mean_y / mean_x)
+        } else {
+            (mean_y, 0.0)
+        };
+
+        Some(Self { overhead_secs, secs_per_residue, samples: samples.len() })
+    }
+
+    fn predict(&self, residues: u64) -> Duration {
+        let secs = self.overhead_secs + self.secs_per_residue * residues as f64;
+        Duration::from_secs_f64(secs.max(0.0))
+    }
+}
+
+fn fit_all<K: std::hash::Hash + Eq>(groups: HashMap<K, Vec<(f64, f64)>>) -> HashMap<K, LinearFit> {
+    groups
+        .into_iter()
+        .filter_map(|(key, samples)| LinearFit::from_samples(&samples).map(|fit| (key, fit)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub runtime: Duration,
+    /// Past runs the estimate is based on
+    pub samples: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct RuntimeEstimator {
+    by_program_database: HashMap<(String, String), LinearFit>,
+    by_program: HashMap<String, LinearFit>,
+    overall: Option<LinearFit>,
+}
+
+impl RuntimeEstimator {
+    /// Builds the estimator from successful runs; `history` is oldest first.
+    pub fn from_history(history: &[HistoryEntry]) -> Self {
+        let mut by_program_database: HashMap<(String, String), Vec<(f64, f64)>> = HashMap::new();
+        let mut by_program: HashMap<String, Vec<(f64, f64)>> = HashMap::new();
+        let mut overall = Vec::new();
+
+        for entry in history.iter().rev().filter(|e| e.succeeded) {
+            let sample = (entry.query_residues as f64, entry.duration_secs);
+            let group = by_program_database
+                .entry((entry.program.clone(), entry.database.clone()))
+                .or_default();
+            if group.len() < MAX_SAMPLES {
+                group.push(sample);
+            }
+            let group = by_program.entry(entry.program.clone()).or_default();
+            if group.len() < MAX_SAMPLES {
+                group.push(sample);
+            }
+            if overall.len() < MAX_SAMPLES {
+                overall.push(sample);
+            }
+        }
+
+        Self {
+            by_program_database: fit_all(by_program_database),
+            by_program: fit_all(by_program),
+            overall: LinearFit::from_samples(&overall),
+        }
+    }
+
+    pub fn estimate(&self, program: &str, database: &str, size: QuerySize) -> Option<Estimate> {
+        let fit = self
+            .by_program_database
+            .get(&(program.to_string(), database.to_string()))
+            .or_else(|| self.by_program.get(program))
+            .or(self.overall.as_ref())?;
+        Some(Estimate { runtime: fit.predict(size.residues), samples: fit.samples })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::tests::entry;
+
+    fn fit(samples: &[(f64, f64)]) -> LinearFit {
+        LinearFit::from_samples(samples).unwrap()
+    }
+
+    fn secs(estimate: Option<Estimate>) -> f64 {
+        estimate.expect("an estimate").runtime.as_secs_f64()
+    }
+
+    fn size(residues: u64) -> QuerySize {
+        QuerySize { sequences: 1, residues }
+    }
+
+    #[test]
+    fn fits_overhead_and_per_residue_cost() {
+        let fit = fit(&[(100.0, 12.0), (200.0, 22.0), (300.0, 32.0)]);
+        assert!((fit.overhead_secs - 2.0).abs() < 1e-9);
+        assert!((fit.secs_per_residue - 0.1).abs() < 1e-9);
+        assert!((fit.predict(400).as_secs_f64() - 42.0).abs() < 1e-9);
+        assert_eq!(fit.samples, 3);
+    }
+
+    #[test]
+    fn degenerate_samples_fall_back_to_mean_or_ratio() {
+        // Longer queries ran faster: no slope, just the mean
+        let negative = fit(&[(100.0, 30.0), (200.0, 10.0)]);
+        assert_eq!((negative.overhead_secs, negative.secs_per_residue), (20.0, 0.0));
+
+        // A single query size: time per residue
+        let same_size = fit(&[(100.0, 10.0), (100.0, 20.0)]);
+        assert_eq!((same_size.overhead_secs, same_size.secs_per_residue), (0.0, 0.15));
+
+        // Nothing to scale by
+        let empty_queries = fit(&[(0.0, 5.0), (0.0, 7.0)]);
+        assert_eq!((empty_queries.overhead_secs, empty_queries.secs_per_residue), (6.0, 0.0));
+
+        // The line would start below zero: ratio through the means instead
+        let steep = fit(&[(100.0, 1.0), (200.0, 100.0)]);
+        assert_eq!(steep.overhead_secs, 0.0);
+        assert!((steep.secs_per_residue - 50.5 / 150.0).abs() < 1e-12);
+
+        assert!(LinearFit::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn only_the_most_recent_runs_count() {
+        // History is oldest first; the old, slow runs fall out of the window
+        let mut history: Vec<HistoryEntry> = (0..50).map(|_| entry("blastn", "nt", 100, 1000.0)).collect();
+        history.extend((0..MAX_SAMPLES).map(|_| entry("blastn", "nt", 100, 10.0)));
+
+        let estimate = RuntimeEstimator::from_history(&history).estimate("blastn", "nt", size(100));
+        assert!((secs(estimate) - 10.0).abs() < 1e-9);
+        assert_eq!(estimate.unwrap().samples, MAX_SAMPLES);
+    }
+
+    #[test]
+    fn falls_back_from_database_to_program_to_everything() {
+        let history = vec![
+            entry("blastn", "nt", 100, 10.0),
+            entry("blastn", "refseq_rna", 100, 30.0),
+            entry("blastp", "nr", 100, 90.0),
+            // Failed runs are not evidence of how long a run takes
+            HistoryEntry { succeeded: false, ..entry("blastn", "nt", 100, 500.0) },
+        ];
+        let estimator = RuntimeEstimator::from_history(&history);
+
+        assert_eq!(secs(estimator.estimate("blastn", "nt", size(100))), 10.0);
+        // Mean of both blastn runs
+        assert_eq!(secs(estimator.estimate("blastn", "core_nt", size(100))), 20.0);
+        // Mean of every successful run
+        assert!((secs(estimator.estimate("tblastx", "nt", size(100))) - 130.0 / 3.0).abs() < 1e-9);
+
+        assert!(RuntimeEstimator::from_history(&[]).estimate("blastn", "nt", size(100)).is_none());
+    }
+
+    #[tokio::test]
+    async fn counts_sequences_and_residues() {
+        let path = std::env::temp_dir().join(format!("nucloflo_query_{}.fasta", std::process::id()));
+        std::fs::write(&path, ">q1 first\nACGT ACGT\nAC\n>q2\nGGG\n").unwrap();
+        let fasta = query_size(&path).await;
+        std::fs::write(&path, "ACGTN\n").unwrap();
+        let bare = query_size(&path).await;
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(fasta.unwrap(), QuerySize { sequences: 2, residues: 13 });
+        assert_eq!(bare.unwrap(), QuerySize { sequences: 1, residues: 5 });
+    }
+}
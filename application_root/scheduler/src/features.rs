@@ -0,0 +1,352 @@
+// -----------------------------
+// BED / GFF3 FEATURE EXPORT
+// -----------------------------
+// Writes hit locations on the subject (genome) as feature files that can be
+// loaded straight into IGV or JBrowse.
+use std::collections::BTreeMap;
+
+use crate::results::{BlastReport, Strand};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureFormat {
+    Bed,
+    Gff3,
+}
+
+/// Which HSP statistic ends up in the score column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreMapping {
+    Bitscore,
+    Evalue,
+    Identity,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeatureOptions {
+    pub format: FeatureFormat,
+    pub score: ScoreMapping,
+    /// Merge overlapping HSPs of the same query on the same subject strand
+    pub merge: bool,
+}
+
+impl FeatureFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bed" => Some(FeatureFormat::Bed),
+            "gff" | "gff3" => Some(FeatureFormat::Gff3),
+            _ => None,
+        }
+    }
+}
+
+impl ScoreMapping {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bitscore" => Some(ScoreMapping::Bitscore),
+            "evalue" => Some(ScoreMapping::Evalue),
+            "identity" => Some(ScoreMapping::Identity),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Feature {
+    seqid: String,
+    query: String,
+    strand: Strand,
+    // 1-based, inclusive
+    start: u64,
+    end: u64,
+    bitscore: f64,
+    evalue: f64,
+    identity: f64,
+}
+
+/// Only searches against nucleotide subjects (blastn, tblastn, tblastx) have
+/// genome coordinates; protein reports (blastp, blastx, DIAMOND, HMMER) are
+/// rejected rather than written with the wrong feature type.
+pub fn export_features(report: &BlastReport, options: &FeatureOptions) -> Result<String, String> {
+    if !has_nucleotide_subjects(&report.program) {
+        let program = if report.program.is_empty() { "unknown" } else { report.program.as_str() };
+        return Err(format!(
+            "Feature export needs nucleotide subjects; {} results are not genome coordinates",
+            program
+        ));
+    }
+
+    let mut features = collect_features(report);
+    if options.merge {
+        features = merge_overlapping(features);
+    }
+
+    Ok(match options.format {
+        FeatureFormat::Bed => to_bed(&features, options.score),
+        FeatureFormat::Gff3 => to_gff3(&features, options.score),
+    })
+}
+
+fn has_nucleotide_subjects(program: &str) -> bool {
+    matches!(
+        program.trim().to_ascii_lowercase().as_str(),
+        "blastn" | "megablast" | "dc-megablast" | "tblastn" | "tblastx"
+    )
+}
+
+fn collect_features(report: &BlastReport) -> Vec<Feature> {
+    let mut features = Vec::new();
+    for (index, query) in report.queries.iter().enumerate() {
+        let query_name = query_name(index, &query.query_def, &query.query_id);
+        for hit in &query.hits {
+            let seqid = if hit.accession.is_empty() { &hit.id } else { &hit.accession };
+            for hsp in &hit.hsps {
+                let (start, end) = hsp.hit_range();
+                // Coordinates are 1-based; 0 means the HSP had none
+                if start == 0 {
+                    continue;
+                }
+                features.push(Feature {
+                    seqid: seqid.clone(),
+                    query: query_name.clone(),
+                    strand: hsp.strand(),
+                    start,
+                    end,
+                    bitscore: hsp.bit_score,
+                    evalue: hsp.evalue,
+                    identity: hsp.percent_identity(),
+                });
+            }
+        }
+    }
+    features
+}
+
+// Feature names may not contain whitespace, so only the FASTA ID is used
+fn query_name(index: usize, query_def: &str, query_id: &str) -> String {
+    let from_def = query_def.split_whitespace().next().filter(|_| query_def != "No definition line");
+    let from_id = query_id.split_whitespace().next();
+    from_def.or(from_id)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("query_{}", index + 1))
+}
+
+fn merge_overlapping(features: Vec<Feature>) -> Vec<Feature> {
+    let mut groups: BTreeMap<(String, String, bool), Vec<Feature>> = BTreeMap::new();
+    for feature in features {
+        let key = (feature.seqid.clone(), feature.query.clone(), feature.strand == Strand::Minus);
+        groups.entry(key).or_default().push(feature);
+    }
+
+    let mut merged = Vec::new();
+    for (_, mut group) in groups {
+        group.sort_by_key(|f| (f.start, f.end));
+        let mut current: Option<Feature> = None;
+        for feature in group {
+            match current.as_mut() {
+                Some(open) if feature.start <= open.end => {
+                    open.end = open.end.max(feature.end);
+                    open.bitscore = open.bitscore.max(feature.bitscore);
+                    open.evalue = open.evalue.min(feature.evalue);
+                    open.identity = open.identity.max(feature.identity);
+                }
+                _ => merged.extend(current.replace(feature)),
+            }
+        }
+        merged.extend(current);
+    }
+    merged
+}
+
+// BED scores are integers in 0..=1000 (IGV shades features by them)
+fn bed_score(feature: &Feature, mapping: ScoreMapping, max_bitscore: f64) -> u32 {
+    let score = match mapping {
+        ScoreMapping::Bitscore if max_bitscore > 0.0 => feature.bitscore / max_bitscore * 1000.0,
+        ScoreMapping::Bitscore => 0.0,
+        ScoreMapping::Evalue if feature.evalue <= 0.0 => 1000.0,
+        ScoreMapping::Evalue => -feature.evalue.log10() * 10.0,
+        ScoreMapping::Identity => feature.identity * 10.0,
+    };
+    score.round().clamp(0.0, 1000.0) as u32
+}
+
+fn gff_score(feature: &Feature, mapping: ScoreMapping) -> String {
+    match mapping {
+        ScoreMapping::Bitscore => format!("{}", feature.bitscore),
+        ScoreMapping::Evalue => format!("{:e}", feature.evalue),
+        ScoreMapping::Identity => format!("{:.2}", feature.identity),
+    }
+}
+
+fn to_bed(features: &[Feature], mapping: ScoreMapping) -> String {
+    let max_bitscore = features.iter().map(|f| f.bitscore).fold(0.0, f64::max);
+    let mut out = String::new();
+    for feature in features {
+        // BED is 0-based, half-open
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            feature.seqid,
+            feature.start.saturating_sub(1),
+            feature.end,
+            feature.query,
+            bed_score(feature, mapping, max_bitscore),
+            feature.strand.symbol(),
+        ));
+    }
+    out
+}
+
+fn to_gff3(features: &[Feature], mapping: ScoreMapping) -> String {
+    let mut out = String::from("##gff-version 3\n");
+    for (index, feature) in features.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\tNucloFlo\tnucleotide_match\t{}\t{}\t{}\t{}\t.\tID=hit{};Name={};bitscore={};evalue={:e};identity={:.2}\n",
+            escape_gff(&feature.seqid),
+            feature.start,
+            feature.end,
+            gff_score(feature, mapping),
+            feature.strand.symbol(),
+            index + 1,
+            escape_gff(&feature.query),
+            feature.bitscore,
+            feature.evalue,
+            feature.identity,
+        ));
+    }
+    out
+}
+
+// Reserved GFF3 characters must be percent-encoded in columns and attributes
+fn escape_gff(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            ';' | '=' | '&' | ',' | '%' | '\t' | '\n' => out.push_str(&format!("%{:02X}", c as u32)),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{Hit, Hsp, QueryResult};
+
+    fn report(program: &str, hsps: Vec<Hsp>) -> BlastReport {
+        BlastReport {
+            program: program.to_string(),
+            database: "core_nt".to_string(),
+            queries: vec![QueryResult {
+                query_id: "Query_1".to_string(),
+                query_def: "seq1 some gene".to_string(),
+                query_len: 100,
+                hits: vec![Hit { accession: "NC_000001".to_string(), hsps, ..Hit::default() }],
+            }],
+        }
+    }
+
+    fn options(format: FeatureFormat) -> FeatureOptions {
+        FeatureOptions { format, score: ScoreMapping::Bitscore, merge: false }
+    }
+
+    #[test]
+    fn bed_is_zero_based_half_open() {
+        let hsp = Hsp { hit_from: 200, hit_to: 101, query_frame: 1, hit_frame: -1, bit_score: 50.0, ..Hsp::default() };
+        let bed = export_features(&report("blastn", vec![hsp]), &options(FeatureFormat::Bed)).unwrap();
+        assert_eq!(bed, "NC_000001\t100\t200\tseq1\t1000\t-\n");
+    }
+
+    #[test]
+    fn hsps_without_coordinates_are_skipped() {
+        let hsp = Hsp { bit_score: 50.0, ..Hsp::default() };
+        let bed = export_features(&report("blastn", vec![hsp]), &options(FeatureFormat::Bed)).unwrap();
+        assert!(bed.is_empty());
+    }
+
+    fn hsp(hit_from: u64, hit_to: u64, bit_score: f64) -> Hsp {
+        let hit_frame = if hit_from > hit_to { -1 } else { 1 };
+        Hsp { hit_from, hit_to, query_frame: 1, hit_frame, bit_score, evalue: 1e-10, identity: 45, align_len: 50, ..Hsp::default() }
+    }
+
+    #[test]
+    fn merge_joins_overlaps_per_strand_only() {
+        let hsps = vec![
+            hsp(1, 100, 100.0),
+            hsp(50, 150, 80.0),
+            // Adjacent, not overlapping
+            hsp(151, 200, 50.0),
+            // Overlaps the first two, but on the other strand
+            hsp(120, 80, 40.0),
+        ];
+        let merged = FeatureOptions { merge: true, ..options(FeatureFormat::Bed) };
+        let bed = export_features(&report("blastn", hsps.clone()), &merged).unwrap();
+        assert_eq!(
+            bed,
+            "NC_000001\t0\t150\tseq1\t1000\t+\n\
+             NC_000001\t150\t200\tseq1\t500\t+\n\
+             NC_000001\t79\t120\tseq1\t400\t-\n"
+        );
+
+        let unmerged = export_features(&report("blastn", hsps), &options(FeatureFormat::Bed)).unwrap();
+        assert_eq!(unmerged.lines().count(), 4);
+    }
+
+    #[test]
+    fn bed_scores_follow_the_mapping() {
+        let bed_scores = |score: ScoreMapping, hsps: Vec<Hsp>| -> Vec<String> {
+            let options = FeatureOptions { score, ..options(FeatureFormat::Bed) };
+            let bed = export_features(&report("blastn", hsps), &options).unwrap();
+            bed.lines().map(|line| line.split('\t').nth(4).unwrap().to_string()).collect()
+        };
+
+        let evalues = [1e-50, 0.0, 1e-200, 5.0].map(|evalue| Hsp { evalue, ..hsp(1, 10, 10.0) });
+        assert_eq!(bed_scores(ScoreMapping::Evalue, evalues.to_vec()), ["500", "1000", "1000", "0"]);
+
+        let identity = vec![hsp(1, 10, 10.0), Hsp { identity: 50, ..hsp(1, 10, 10.0) }];
+        assert_eq!(bed_scores(ScoreMapping::Identity, identity), ["900", "1000"]);
+
+        let bitscores = vec![hsp(1, 10, 200.0), hsp(1, 10, 50.0)];
+        assert_eq!(bed_scores(ScoreMapping::Bitscore, bitscores), ["1000", "250"]);
+    }
+
+    #[test]
+    fn gff3_line_escapes_reserved_characters() {
+        let mut report = report("tblastn", vec![hsp(101, 200, 50.0)]);
+        report.queries[0].query_def = "seq;1=a,b gene".to_string();
+        let options = FeatureOptions { score: ScoreMapping::Identity, ..options(FeatureFormat::Gff3) };
+
+        let gff = export_features(&report, &options).unwrap();
+        assert_eq!(
+            gff,
+            "##gff-version 3\n\
+             NC_000001\tNucloFlo\tnucleotide_match\t101\t200\t90.00\t+\t.\t\
+             ID=hit1;Name=seq%3B1%3Da%2Cb;bitscore=50;evalue=1e-10;identity=90.00\n"
+        );
+        assert_eq!(escape_gff("a%b&c\td"), "a%25b%26c%09d");
+    }
+
+    #[test]
+    fn gff_scores_follow_the_mapping() {
+        let feature = Feature {
+            seqid: "NC_000001".to_string(),
+            query: "seq1".to_string(),
+            strand: Strand::Plus,
+            start: 1,
+            end: 10,
+            bitscore: 55.5,
+            evalue: 2.5e-7,
+            identity: 96.666,
+        };
+        assert_eq!(gff_score(&feature, ScoreMapping::Bitscore), "55.5");
+        assert_eq!(gff_score(&feature, ScoreMapping::Evalue), "2.5e-7");
+        assert_eq!(gff_score(&feature, ScoreMapping::Identity), "96.67");
+    }
+
+    #[test]
+    fn protein_reports_are_rejected() {
+        let hsp = Hsp { hit_from: 1, hit_to: 50, ..Hsp::default() };
+        for program in ["blastp", "blastx", "hmmscan", ""] {
+            assert!(export_features(&report(program, vec![hsp.clone()]), &options(FeatureFormat::Gff3)).is_err());
+        }
+    }
+}
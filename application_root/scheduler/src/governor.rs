@@ -0,0 +1,342 @@
+// -----------------------------
+// REQUEST GOVERNOR
+// -----------------------------
+// Keeps dispatches to a remote engine within its request rate and
+// concurrency limits, and backs off when the service pushes back.
+//
+// A governor lives in one process, but the UI starts a scheduler process per
+// job. Governors given a state file (`shared`) therefore also agree on request
+// spacing and cool-downs through it, under an exclusive file lock. The
+// concurrency limit stays per process.
+//
+// Within a process, requests get their slots strictly in ticket order, so
+// the order jobs are dispatched in is the order they reach the engine.
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use crate::engines::RateLimitConfig;
+use crate::{BlastEngine, BlastEngineError, BlastExecutionRequest, BlastResult};
+
+/// HTTP statuses that mean "slow down / try again later". A plain 500 is
+/// not one of them: the Python engine answers 500 for any exception,
+/// including bad input, and retrying that only burns the service's quota.
+pub fn is_throttling_status(status: u16) -> bool {
+    matches!(status, 429 | 502 | 503 | 504)
+}
+
+/// application_root/governors/<engine id>.state
+pub fn state_path(root: &Path, engine_id: &str) -> PathBuf {
+    root.join("governors").join(format!("{}.state", engine_id))
+}
+
+pub struct Governor {
+    limits: RateLimitConfig,
+    slots: Arc<Semaphore>,
+    state: Mutex<GovernorState>,
+    shared: Option<PathBuf>,
+    next_ticket: AtomicU64,
+    // Ticket whose turn it is to take a slot
+    serving: watch::Sender<u64>,
+}
+
+/// A place in a governor's queue; see `Governor::ticket`.
+#[derive(Debug)]
+pub struct Ticket(u64);
+
+struct GovernorState {
+    next_request: Instant,
+    cooldown_until: Option<Instant>,
+}
+
+impl Governor {
+    pub fn new(limits: RateLimitConfig) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(limits.max_concurrent.max(1))),
+            state: Mutex::new(GovernorState {
+                next_request: Instant::now(),
+                cooldown_until: None,
+            }),
+            limits,
+            shared: None,
+            next_ticket: AtomicU64::new(0),
+            serving: watch::Sender::new(0),
+        }
+    }
+
+    /// Shares request spacing and cool-downs with every governor, in any
+    /// process, using the same state file.
+    pub fn shared(mut self, path: PathBuf) -> Self {
+        self.shared = Some(path);
+        self
+    }
+
+    pub fn interval(&self) -> Duration {
+        if self.limits.requests_per_sec > 0.0 {
+            Duration::from_secs_f64(1.0 / self.limits.requests_per_sec)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    /// Takes the next place in the queue. Every ticket must be passed to
+    /// `acquire_in_turn`, or later tickets wait forever.
+    pub fn ticket(&self) -> Ticket {
+        Ticket(self.next_ticket.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Takes a ticket and waits for its turn (see `acquire_in_turn`).
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        self.acquire_in_turn(self.ticket()).await
+    }
+
+    /// Waits until every earlier ticket has its slot, then for a concurrency
+    /// slot, then for the next free request slot. The returned permit holds
+    /// the concurrency slot until dropped.
+    pub async fn acquire_in_turn(&self, ticket: Ticket) -> OwnedSemaphorePermit {
+        let mut serving = self.serving.subscribe();
+        serving
+            .wait_for(|&serving| serving == ticket.0)
+            .await
+            .expect("governor owns the turn sender");
+        let permit = Arc::clone(&self.slots)
+            .acquire_owned()
+            .await
+            .expect("governor semaphore is never closed");
+
+        let start = {
+            let mut state = self.state.lock().expect("governor state lock poisoned");
+            let mut start = state.next_request.max(Instant::now());
+            if let Some(until) = state.cooldown_until {
+                start = start.max(until);
+            }
+            state.next_request = start + self.interval();
+            start
+        };
+        let start = match &self.shared {
+            Some(path) => self.update_shared(path, |shared| {
+                let start = shared.next_request.max(shared.cooldown_until).max(unix_millis(start));
+                shared.next_request = start + self.interval().as_millis() as u64;
+                start
+            }).map_or(start, instant_at),
+            None => start,
+        };
+        // Only now, so the next ticket's request slot comes after this one
+        self.serving.send_modify(|serving| *serving += 1);
+        tokio::time::sleep_until(start).await;
+        permit
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.limits.max_retries
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.limits.max_concurrent.max(1)
+    }
+
+    /// Holds back every request to this engine for the configured cool-down.
+    pub fn cool_down(&self) {
+        let until = Instant::now() + Duration::from_secs(self.limits.cooldown_secs);
+        let mut state = self.state.lock().expect("governor state lock poisoned");
+        state.cooldown_until = Some(state.cooldown_until.map_or(until, |current| current.max(until)));
+        drop(state);
+
+        if let Some(path) = &self.shared {
+            self.update_shared(path, |shared| {
+                shared.cooldown_until = shared.cooldown_until.max(unix_millis(until));
+            });
+        }
+    }
+
+    // Returns None, leaving this governor to its local state, if the file
+    // can't be used
+    fn update_shared<T>(&self, path: &Path, update: impl FnOnce(&mut SharedState) -> T) -> Option<T> {
+        update_state_file(path, update)
+            .map_err(|err| crate::log!("⚠️ Rate limit state {:?} not shared: {}", path, err))
+            .ok()
+    }
+}
+
+// The file lock is only held for one read and one write, so this blocks the
+// runtime no longer than any small local file access
+fn update_state_file<T>(path: &Path, update: impl FnOnce(&mut SharedState) -> T) -> std::io::Result<T> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+    file.lock()?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    let mut shared = SharedState::parse(&text);
+    let value = update(&mut shared);
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(shared.to_line().as_bytes())?;
+    Ok(value)
+}
+
+// Unix milliseconds, so separate processes can compare them
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SharedState {
+    next_request: u64,
+    cooldown_until: u64,
+}
+
+impl SharedState {
+    // "<next_request> <cooldown_until>"; anything unreadable is a fresh state
+    fn parse(text: &str) -> Self {
+        let mut fields = text.split_whitespace().map(|field| field.parse().unwrap_or(0));
+        Self {
+            next_request: fields.next().unwrap_or(0),
+            cooldown_until: fields.next().unwrap_or(0),
+        }
+    }
+
+    fn to_line(&self) -> String {
+        format!("{} {}\n", self.next_request, self.cooldown_until)
+    }
+}
+
+fn unix_millis(instant: Instant) -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let at = match instant.checked_duration_since(Instant::now()) {
+        Some(ahead) => now + ahead,
+        None => now.saturating_sub(Instant::now() - instant),
+    };
+    at.as_millis() as u64
+}
+
+fn instant_at(unix_millis: u64) -> Instant {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+    Instant::now() + Duration::from_millis(unix_millis.saturating_sub(now))
+}
+
+/// Runs the request, going through the governor when the engine has one.
+/// Throttled requests are retried after a cool-down.
+pub async fn execute(
+    engine: &(dyn BlastEngine + Send + Sync),
+    request: BlastExecutionRequest,
+    governor: Option<&Governor>,
+) -> Result<BlastResult, BlastEngineError> {
+    let governor = governor.map(|governor| (governor, governor.ticket()));
+    execute_timed(engine, request, governor).await.0
+}
+
+/// Like `execute` with a ticket taken in advance, so the first attempt runs
+/// in dispatch order; retries queue again at the back. Also returns the time
+/// spent in the engine on the final attempt, without rate-limit waits and
+/// cool-downs.
+pub async fn execute_timed(
+    engine: &(dyn BlastEngine + Send + Sync),
+    request: BlastExecutionRequest,
+    governor: Option<(&Governor, Ticket)>,
+) -> (Result<BlastResult, BlastEngineError>, Duration) {
+    let Some((governor, first_ticket)) = governor else {
+        let started = Instant::now();
+        let result = engine.execute(request).await;
+        return (result, started.elapsed());
+    };
+
+    let mut attempt = 0;
+    let mut ticket = first_ticket;
+    loop {
+        let permit = governor.acquire_in_turn(ticket).await;
+        let started = Instant::now();
+        let result = engine.execute(request.clone()).await;
+        let elapsed = started.elapsed();
+        drop(permit);
+
+        match result {
+            Err(BlastEngineError::Throttled(reason)) if attempt < governor.limits.max_retries => {
+                attempt += 1;
+                crate::log!(
+                    "⏳ {} throttled job {} ({}), cooling down for {}s",
+                    engine.name(),
+                    request.job_id,
+                    reason,
+                    governor.limits.cooldown_secs
+                );
+                governor.cool_down();
+                ticket = governor.ticket();
+            }
+            other => return (other, elapsed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(requests_per_sec: f64, cooldown_secs: u64) -> RateLimitConfig {
+        RateLimitConfig { requests_per_sec, max_concurrent: 4, cooldown_secs, max_retries: 0 }
+    }
+
+    fn temp_state(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("nucloflo_governor_{}_{}", std::process::id(), name)).join("python.state")
+    }
+
+    #[tokio::test]
+    async fn governors_sharing_a_state_file_space_their_requests() {
+        let path = temp_state("spacing");
+        // Stand-ins for two scheduler processes
+        let first = Governor::new(limits(5.0, 0)).shared(path.clone());
+        let second = Governor::new(limits(5.0, 0)).shared(path.clone());
+
+        let started = Instant::now();
+        drop(first.acquire().await);
+        drop(second.acquire().await);
+        let elapsed = started.elapsed();
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+
+        assert!(elapsed >= Duration::from_millis(180), "second request after {:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn cool_downs_are_shared() {
+        let path = temp_state("cooldown");
+        let first = Governor::new(limits(0.0, 60)).shared(path.clone());
+        first.cool_down();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        let shared = SharedState::parse(&text);
+        let now = unix_millis(Instant::now());
+        assert!(shared.cooldown_until >= now + 59_000, "{:?}", shared);
+    }
+
+    #[test]
+    fn unreadable_state_is_a_fresh_one() {
+        assert_eq!(SharedState::parse("garbage"), SharedState::default());
+        assert_eq!(SharedState::parse("12 34\n"), SharedState { next_request: 12, cooldown_until: 34 });
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn slots_are_handed_out_in_ticket_order() {
+        let governor = Arc::new(Governor::new(RateLimitConfig { max_concurrent: 1, ..limits(0.0, 0) }));
+        let served = Arc::new(Mutex::new(Vec::new()));
+        let tickets: Vec<Ticket> = (0..4).map(|_| governor.ticket()).collect();
+
+        // Later tickets start waiting first
+        let mut handles = Vec::new();
+        for (index, ticket) in tickets.into_iter().enumerate().rev() {
+            let (governor, served) = (Arc::clone(&governor), Arc::clone(&served));
+            handles.push(tokio::spawn(async move {
+                let _permit = governor.acquire_in_turn(ticket).await;
+                served.lock().unwrap().push(index);
+            }));
+            tokio::task::yield_now().await;
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(*served.lock().unwrap(), [0, 1, 2, 3]);
+    }
+}
+
@@ -0,0 +1,134 @@
+// -----------------------------
+// JOB HISTORY
+// -----------------------------
+// One JSON line per finished job in application_root/history.jsonl. The
+// runtime estimator learns from it and fair-share reads past usage from it.
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub job_id: u64,
+    pub program: String,
+    pub database: String,
+    pub engine: String,
+    /// Fair-share group; empty when the job had no owner
+    #[serde(default)]
+    pub owner: String,
+    pub query_sequences: u64,
+    pub query_residues: u64,
+    /// Time spent in the engine, excluding rate-limit waits
+    pub duration_secs: f64,
+    pub succeeded: bool,
+    /// Unix time the job finished
+    pub finished_at: u64,
+}
+
+impl HistoryEntry {
+    pub fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+    }
+}
+
+pub fn history_path(root: &Path) -> PathBuf {
+    root.join("history.jsonl")
+}
+
+/// A missing history file is an empty history. Lines that don't parse are
+/// skipped so one bad write can't take the estimator down.
+pub fn load(path: &Path) -> Result<Vec<HistoryEntry>, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Cannot read {:?}: {}", path, e)),
+    };
+    Ok(text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+pub async fn append(path: &Path, entry: &HistoryEntry) -> Result<(), String> {
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| format!("Cannot serialize history entry: {}", e))?;
+    line.push('\n');
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|e| format!("Cannot open {:?}: {}", path, e))?;
+    // tokio finishes the write in the background; flush so it lands before we return
+    file.write_all(line.as_bytes())
+        .await
+        .map_err(|e| format!("Cannot write {:?}: {}", path, e))?;
+    file.flush()
+        .await
+        .map_err(|e| format!("Cannot write {:?}: {}", path, e))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn entry(program: &str, database: &str, residues: u64, secs: f64) -> HistoryEntry {
+        HistoryEntry {
+            job_id: 1,
+            program: program.to_string(),
+            database: database.to_string(),
+            engine: "Python engine".to_string(),
+            owner: String::new(),
+            query_sequences: 1,
+            query_residues: residues,
+            duration_secs: secs,
+            succeeded: true,
+            finished_at: 0,
+        }
+    }
+
+    fn temp_history(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("nucloflo_history_{}_{}.jsonl", std::process::id(), name))
+    }
+
+    #[test]
+    fn missing_history_is_empty() {
+        assert!(load(&temp_history("missing")).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn appended_entries_load_back_in_order() {
+        let path = temp_history("append");
+        let _ = std::fs::remove_file(&path);
+        append(&path, &entry("blastn", "nt", 100, 1.5)).await.unwrap();
+        append(&path, &HistoryEntry { owner: "lab-a".to_string(), ..entry("blastp", "nr", 300, 9.0) }).await.unwrap();
+
+        let loaded = load(&path);
+        let _ = std::fs::remove_file(&path);
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!((loaded[0].program.as_str(), loaded[0].duration_secs), ("blastn", 1.5));
+        assert_eq!((loaded[1].owner.as_str(), loaded[1].query_residues), ("lab-a", 300));
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped_and_owner_defaults() {
+        let path = temp_history("malformed");
+        let good = serde_json::to_string(&entry("blastn", "nt", 100, 2.0)).unwrap();
+        // Written before owners existed
+        let ownerless = good.replace(r#""owner":"","#, "");
+        std::fs::write(&path, format!("{}\n{{\"job_id\": 2, truncated\n\n   \nnot json\n{}\n", good, ownerless)).unwrap();
+
+        let loaded = load(&path);
+        let _ = std::fs::remove_file(&path);
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded.iter().all(|e| e.owner.is_empty() && e.duration_secs == 2.0));
+    }
+}
@@ -0,0 +1,300 @@
+// -----------------------------
+// HMMER ENGINE
+// -----------------------------
+// Profile searches: hmmscan (sequences against a profile library such as
+// Pfam) and hmmsearch (a profile against a sequence database). Per-domain
+// tabular output (--domtblout) is converted into the common result types.
+use std::path::Path;
+use tokio::fs;
+use tokio::process::Command;
+
+use crate::databases::{ensure_profile_pressed, DatabaseKind};
+use crate::results::{BlastReport, Hit, Hsp, QueryResult, ResultParseError};
+use crate::{
+    BlastEngine, BlastEngineError, BlastExecutionRequest, BlastInput, BlastResult,
+    BlastType, ResultOutput, ResultStatus,
+};
+
+/// Fixed columns of a --domtblout row; the target description follows.
+const DOMTBL_COLUMNS: usize = 22;
+
+pub struct HmmerEngine;
+
+#[async_trait::async_trait]
+impl BlastEngine for HmmerEngine {
+    fn name(&self) -> &'static str { "HMMER engine" }
+
+    async fn execute(&self, request: BlastExecutionRequest) -> Result<BlastResult, BlastEngineError> {
+        crate::log!("🧬 HMMER engine executing job {}", request.job_id);
+
+        let program = match request.blast_type {
+            BlastType::HmmScan | BlastType::HmmSearch => request.blast_type.to_string(),
+            _ => return Err(BlastEngineError::UnsupportedFormat),
+        };
+        let input_path = match request.input {
+            BlastInput::FilePath(ref path) => path,
+            _ => return Err(BlastEngineError::InvalidInput(
+                "HMMER engine requires file input".to_string()
+            )),
+        };
+        if !input_path.exists() {
+            return Err(BlastEngineError::InvalidInput(
+                format!("Input file does not exist: {:?}", input_path)
+            ));
+        }
+
+        // hmmscan searches a profile library, hmmsearch a sequence database
+        let expected = match request.blast_type {
+            BlastType::HmmScan => DatabaseKind::Profile,
+            _ => DatabaseKind::Protein,
+        };
+        if let Some(kind) = request.database_kind.filter(|kind| *kind != expected) {
+            return Err(BlastEngineError::InvalidInput(format!(
+                "{} needs a {:?} database, but {} is registered as {:?}",
+                program, expected, request.database, kind
+            )));
+        }
+
+        // hmmscan <profiles> <seqs>; hmmsearch <profile> <seqdb>
+        let database = Path::new(&request.database);
+        let (first, second) = match request.blast_type {
+            BlastType::HmmScan => {
+                ensure_profile_pressed(database).await?;
+                (database, input_path.as_path())
+            }
+            _ => {
+                if !database.exists() {
+                    return Err(BlastEngineError::DatabaseUnavailable);
+                }
+                (input_path.as_path(), database)
+            }
+        };
+
+        let output_dir = &request.output_dir;
+        fs::create_dir_all(output_dir).await
+            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Cannot create output dir: {}", e)))?;
+        let output_path = output_dir.join(format!("hmmer_{}.domtblout", request.job_id));
+        let report_path = output_dir.join(format!("hmmer_{}.txt", request.job_id));
+
+        let output = Command::new(program)
+            .arg("--domtblout").arg(&output_path)
+            .arg("-o").arg(&report_path)
+            .arg(first)
+            .arg(second)
+            .output()
+            .await
+            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Failed to run {}: {}", program, e)))?;
+
+        if !output.status.success() {
+            return Err(BlastEngineError::ExecutionFailed(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        crate::log!("✅ {} completed successfully", program);
+
+        Ok(BlastResult {
+            job_id: request.job_id,
+            status: ResultStatus::Success,
+            output: ResultOutput::FilePath(output_path),
+        })
+    }
+}
+
+/// Parses HMMER `--domtblout` output. Each domain becomes one HSP; the
+/// sequence side of the alignment uses the ali coordinates and the profile
+/// side the hmm coordinates, whichever of query/target each one is.
+pub fn parse_domtblout(text: &str) -> Result<BlastReport, ResultParseError> {
+    let mut report = BlastReport::default();
+
+    // The trailing comment block records how the search was run
+    for comment in text.lines().filter_map(|line| line.strip_prefix('#')) {
+        let comment = comment.trim();
+        if let Some(program) = comment.strip_prefix("Program:") {
+            report.program = program.trim().to_string();
+        } else if let Some(target) = comment.strip_prefix("Target file:") {
+            report.database = target.trim().to_string();
+        }
+    }
+    // In hmmscan the target is the profile; in hmmsearch the query is
+    let profile_is_target = report.program != "hmmsearch";
+
+    for (line_no, line) in text.lines().enumerate() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+
+        let cols: Vec<&str> = line.split_whitespace().collect();
+        if cols.len() < DOMTBL_COLUMNS {
+            return Err(ResultParseError::Malformed(format!(
+                "Line {}: expected at least {} columns, found {}",
+                line_no + 1,
+                DOMTBL_COLUMNS,
+                cols.len()
+            )));
+        }
+        let number = |index: usize| -> Result<f64, ResultParseError> {
+            cols[index].parse().map_err(|_| ResultParseError::InvalidValue(
+                format!("line {} column {}: '{}'", line_no + 1, index + 1, cols[index])
+            ))
+        };
+        let description = cols[DOMTBL_COLUMNS..].join(" ");
+
+        let (target, target_acc, query) = (cols[0], cols[1], cols[3]);
+        if report.queries.last().is_none_or(|q| q.query_id != query) {
+            report.queries.push(QueryResult {
+                query_id: query.to_string(),
+                query_def: query.to_string(),
+                query_len: number(5)? as u64,
+                hits: Vec::new(),
+            });
+        }
+        let query_result = report.queries.last_mut().expect("query was just pushed");
+
+        if query_result.hits.last().is_none_or(|h| h.id != target) {
+            query_result.hits.push(Hit {
+                id: target.to_string(),
+                def: if description == "-" { String::new() } else { description },
+                accession: if target_acc == "-" { String::new() } else { target_acc.to_string() },
+                len: number(2)? as u64,
+                hsps: Vec::new(),
+            });
+        }
+        let hit = query_result.hits.last_mut().expect("hit was just pushed");
+
+        let (hmm_from, hmm_to) = (number(15)? as u64, number(16)? as u64);
+        let (ali_from, ali_to) = (number(17)? as u64, number(18)? as u64);
+        let ((query_from, query_to), (hit_from, hit_to)) = if profile_is_target {
+            ((ali_from, ali_to), (hmm_from, hmm_to))
+        } else {
+            ((hmm_from, hmm_to), (ali_from, ali_to))
+        };
+
+        hit.hsps.push(Hsp {
+            bit_score: number(13)?,
+            score: number(13)?,
+            evalue: number(12)?,
+            query_from,
+            query_to,
+            hit_from,
+            hit_to,
+            align_len: ali_to.saturating_sub(ali_from) + 1,
+            ..Hsp::default()
+        });
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "\
+#                                                                            --- full sequence --- -------------- this domain -------------   hmm coord   ali coord   env coord
+# target name        accession   tlen query name           accession   qlen   E-value  score  bias   #  of  c-Evalue  i-Evalue  score  bias  from    to  from    to  from    to  acc description of target
+#------------------- ---------- ----- -------------------- ---------- ----- --------- ------ ----- --- --- --------- --------- ------ ----- ----- ----- ----- ----- ----- ----- ---- ---------------------
+";
+
+    fn hmmscan_output() -> String {
+        format!("{}{}", HEADER, "\
+Pkinase              PF00069.28   264 sp|P00533|EGFR_HUMAN -           1210   1.2e-53  181.9   0.0   1   1   4.1e-57   2.3e-53  181.0   0.0     3   259   714   963   712   965 0.94 Protein kinase domain
+Recep_L_domain       PF01030.27   112 sp|P00533|EGFR_HUMAN -           1210   2.1e-60  203.3   0.2   1   2   1.9e-32   1.1e-28  100.4   0.0     1   111    57   168    57   169 0.98 Receptor L domain
+Recep_L_domain       PF01030.27   112 sp|P00533|EGFR_HUMAN -           1210   2.1e-60  203.3   0.2   2   2   3.0e-32   1.7e-28   99.8   0.0     1   111   361   480   361   481 0.97 Receptor L domain
+Pkinase              PF00069.28   264 sp|P04626|ERBB2_HUMAN -          1255   3.3e-52  177.2   0.0   1   1   1.1e-55   6.4e-52  176.3   0.0     2   259   722   970   721   972 0.93 Protein kinase domain
+#
+# Program:         hmmscan
+# Version:         3.4 (Aug 2023)
+# Pipeline mode:   SCAN
+# Query file:      erbb.fasta
+# Target file:     Pfam-A.hmm
+# [ok]
+")
+    }
+
+    fn hmmsearch_output() -> String {
+        format!("{}{}", HEADER, "\
+sp|P00533|EGFR_HUMAN -           1210 Pkinase              PF00069.28   264   1.2e-53  181.9   0.0   1   1   4.1e-57   2.3e-53  181.0   0.0     3   259   714   963   712   965 0.94 Epidermal growth factor receptor
+#
+# Program:         hmmsearch
+# Version:         3.4 (Aug 2023)
+# Pipeline mode:   SEARCH
+# Query file:      Pkinase.hmm
+# Target file:     uniprot_sprot.fasta
+# [ok]
+")
+    }
+
+    #[test]
+    fn hmmscan_groups_domains_by_query_and_profile() {
+        let report = parse_domtblout(&hmmscan_output()).unwrap();
+        assert_eq!(report.program, "hmmscan");
+        assert_eq!(report.database, "Pfam-A.hmm");
+
+        let queries: Vec<&str> = report.queries.iter().map(|q| q.query_id.as_str()).collect();
+        assert_eq!(queries, ["sp|P00533|EGFR_HUMAN", "sp|P04626|ERBB2_HUMAN"]);
+        assert_eq!(report.queries[0].query_len, 1210);
+
+        let hits = &report.queries[0].hits;
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].accession, "PF00069.28");
+        assert_eq!(hits[0].def, "Protein kinase domain");
+        assert_eq!(hits[0].len, 264);
+        assert_eq!(hits[1].hsps.len(), 2);
+    }
+
+    #[test]
+    fn hmmscan_puts_sequence_coordinates_on_the_query() {
+        let report = parse_domtblout(&hmmscan_output()).unwrap();
+        let hsp = &report.queries[0].hits[0].hsps[0];
+        assert_eq!(hsp.evalue, 2.3e-53);
+        assert_eq!(hsp.bit_score, 181.0);
+        assert_eq!((hsp.query_from, hsp.query_to), (714, 963));
+        assert_eq!((hsp.hit_from, hsp.hit_to), (3, 259));
+        assert_eq!(hsp.align_len, 250);
+    }
+
+    #[test]
+    fn hmmsearch_puts_sequence_coordinates_on_the_hit() {
+        let report = parse_domtblout(&hmmsearch_output()).unwrap();
+        assert_eq!(report.program, "hmmsearch");
+        assert_eq!(report.queries[0].query_id, "Pkinase");
+        assert_eq!(report.queries[0].query_len, 264);
+
+        let hit = &report.queries[0].hits[0];
+        assert_eq!(hit.id, "sp|P00533|EGFR_HUMAN");
+        assert_eq!(hit.accession, "");
+        assert_eq!(hit.len, 1210);
+        let hsp = &hit.hsps[0];
+        assert_eq!((hsp.query_from, hsp.query_to), (3, 259));
+        assert_eq!((hsp.hit_from, hsp.hit_to), (714, 963));
+    }
+
+    #[test]
+    fn rejects_short_rows() {
+        let text = "Pkinase PF00069.28 264 query - 100 1e-5\n";
+        assert!(matches!(parse_domtblout(text), Err(ResultParseError::Malformed(_))));
+    }
+
+    #[tokio::test]
+    async fn hmmscan_refuses_non_profile_databases() {
+        let input = std::env::temp_dir().join(format!("nucloflo_hmmer_test_{}.fasta", std::process::id()));
+        std::fs::write(&input, ">q\nMKV\n").unwrap();
+
+        let request = BlastExecutionRequest {
+            job_id: 1,
+            blast_type: BlastType::HmmScan,
+            database: "/data/uniprot_sprot.fasta".to_string(),
+            database_kind: Some(DatabaseKind::Protein),
+            input: BlastInput::FilePath(input.clone()),
+            parameters: crate::BlastParameters,
+            output_dir: std::env::temp_dir(),
+            compress_output: false,
+        };
+        let result = HmmerEngine.execute(request).await;
+        std::fs::remove_file(&input).unwrap();
+        assert!(matches!(result, Err(BlastEngineError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,689 @@
+// Scheduler library: job model, engines and the dispatching scheduler.
+// The `scheduler` binary is a thin CLI over this; pipelines embedding
+// NucloFlo (and tests, see `testing`) use it directly.
+
+// Standard library imports
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::env;
+use tokio::fs;
+use tokio::process::Command;
+
+/// `println!` with known credentials scrubbed; use for every log line.
+#[macro_export]
+macro_rules! log {
+    ($($arg:tt)*) => {
+        println!("{}", $crate::credentials::redact(&format!($($arg)*)))
+    };
+}
+
+/// `eprintln!` with known credentials scrubbed.
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        eprintln!("{}", $crate::credentials::redact(&format!($($arg)*)))
+    };
+}
+
+pub mod compression;
+pub mod credentials;
+pub mod databases;
+pub mod diamond;
+pub mod diff;
+pub mod engines;
+pub mod estimator;
+pub mod features;
+pub mod governor;
+pub mod history;
+pub mod hmmer;
+pub mod metadata;
+pub mod ncbi;
+pub mod planning;
+pub mod results;
+pub mod streaming;
+pub mod testing;
+pub mod visualization;
+
+// -----------------------------
+// Structs
+// -----------------------------
+pub struct Job {
+    pub id: u32,
+    pub name: String,
+    pub schedule: std::time::Duration,
+    pub state: JobState,
+    pub input_path: PathBuf,
+    pub database: String,
+    pub output_path: PathBuf,
+    pub program: BlastType,
+    pub metadata: BTreeMap<String, String>,
+    /// Gzip the result file; None uses the engine's `compress_output` setting
+    pub compress_output: Option<bool>,
+}
+
+#[derive(Clone)]
+pub struct BlastExecutionRequest {
+    pub job_id: u64,
+    pub blast_type: BlastType,
+    pub database: String,
+    /// Set when the database is registered; unregistered names are unchecked
+    pub database_kind: Option<databases::DatabaseKind>,
+    pub input: BlastInput,
+    pub parameters: BlastParameters,
+    /// Where the engine writes its result file
+    pub output_dir: PathBuf,
+    /// Engines that stream their output may gzip it as it is written;
+    /// otherwise the scheduler compresses the finished file
+    pub compress_output: bool,
+}
+
+pub struct RustProcessEngine;
+pub struct PythonBlastEngine;
+
+#[derive(Clone)]
+pub struct BlastParameters;
+
+pub struct Scheduler {
+    queue: Vec<Job>,
+    join_handle: Vec<tokio::task::JoinHandle<JobOutcome>>,
+    // Holds outputs/ and the registry files
+    root: PathBuf,
+    rust_engine: Arc<dyn BlastEngine + Send + Sync>,
+    python_engine: Arc<dyn BlastEngine + Send + Sync>,
+    diamond_engine: Arc<dyn BlastEngine + Send + Sync>,
+    hmmer_engine: Arc<dyn BlastEngine + Send + Sync>,
+    databases: databases::DatabaseRegistry,
+    // Keyed by engine id; only engines with a rate limit have one
+    governors: HashMap<&'static str, Arc<governor::Governor>>,
+    // Ids of engines configured to gzip their results by default
+    compressed_engines: HashSet<&'static str>,
+    policy: planning::QueuePolicy,
+    history: Vec<history::HistoryEntry>,
+    estimator: estimator::RuntimeEstimator,
+}
+
+#[derive(Debug)]
+pub struct BlastResult {
+    pub job_id: u64,
+    pub status: ResultStatus,
+    pub output: ResultOutput,
+}
+
+#[derive(Debug)]
+pub struct JobOutcome {
+    pub job_id: u32,
+    pub result: Result<BlastResult, BlastEngineError>,
+}
+
+impl Job {
+    // Create job from the provided input path
+    // UI provides: input_path
+    // Scheduler fills in: id, name, schedule, program, database, state, output_path
+    pub fn new(id: u32, input_path: PathBuf) -> Self {
+        Job {
+            id,
+            name: format!("BLAST Job for {}", input_path.file_name().unwrap_or_default().to_string_lossy()),
+            schedule: std::time::Duration::from_secs(0),
+            program: BlastType::BlastN,  // Default to BlastN
+            database: "nt".to_string(),   // Default to nucleotide database
+            state: JobState::Queued,
+            input_path,
+            output_path: PathBuf::new(),  // Will be set by engine
+            metadata: BTreeMap::new(),
+            compress_output: None,
+        }
+    }
+}
+
+// -----------------------------
+// Enums
+// -----------------------------
+
+pub enum JobState { 
+    Queued, 
+    Running, 
+    Completed 
+}
+
+#[derive(Debug, Clone)]
+pub enum BlastType { 
+    BlastN, 
+    BlastP, 
+    BlastX, 
+    TBlastN,
+    TBlastX,
+    // HMMER profile searches
+    HmmScan,
+    HmmSearch,
+}
+
+impl BlastType {
+    pub fn to_string(&self) -> &str {
+        match self {
+            BlastType::BlastN => "blastn",
+            BlastType::BlastP => "blastp",
+            BlastType::BlastX => "blastx",
+            BlastType::TBlastN => "tblastn",
+            BlastType::TBlastX => "tblastx",
+            BlastType::HmmScan => "hmmscan",
+            BlastType::HmmSearch => "hmmsearch",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "blastn" => Some(BlastType::BlastN),
+            "blastp" => Some(BlastType::BlastP),
+            "blastx" => Some(BlastType::BlastX),
+            "tblastn" => Some(BlastType::TBlastN),
+            "tblastx" => Some(BlastType::TBlastX),
+            "hmmscan" => Some(BlastType::HmmScan),
+            "hmmsearch" => Some(BlastType::HmmSearch),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BlastInput { 
+    FilePath(PathBuf), 
+    RawBytes(Vec<u8>) 
+}
+
+#[derive(Debug)]
+pub enum ResultStatus { 
+    Success,
+    Failed 
+}
+
+#[derive(Debug)]
+pub enum ResultOutput { 
+    FilePath(PathBuf) 
+}
+
+#[derive(Debug, Clone)]
+pub enum BlastEngineError {
+    InvalidInput(String),
+    UnsupportedFormat,
+    DatabaseUnavailable,
+    ExecutionFailed(String),
+    Timeout,
+    // Remote service answered 429/502/503/504; worth retrying after a cool-down
+    Throttled(String),
+}
+
+// -----------------------------
+// Traits 
+// -----------------------------
+#[async_trait::async_trait]
+pub trait BlastEngine {
+    async fn execute(&self, request: BlastExecutionRequest) -> Result<BlastResult, BlastEngineError>;
+    fn name(&self) -> &'static str;
+}
+
+// application_root/, three levels above target/<profile>/scheduler
+pub fn app_root() -> Result<PathBuf, BlastEngineError> {
+    let exe_path = env::current_exe()
+        .map_err(|e| BlastEngineError::ExecutionFailed(format!("Cannot get exe path: {}", e)))?;
+    exe_path.parent()
+        .and_then(|p| p.parent())
+        .and_then(|p| p.parent())
+        .map(|p| p.to_path_buf())
+        .ok_or(BlastEngineError::ExecutionFailed("Cannot determine app root".to_string()))
+}
+
+pub fn load_engine_registry() -> engines::EngineRegistry {
+    app_root()
+        .map_err(|e| format!("{:?}", e))
+        .and_then(|root| engines::EngineRegistry::load(&root.join("engines.json")))
+        .unwrap_or_else(|err| {
+            log!("⚠️ Engine registry not loaded, using defaults: {}", err);
+            engines::EngineRegistry::default()
+        })
+}
+
+// Resolves the credential an engine config names for `purpose`, if any
+pub async fn engine_credential(engine_id: &str, purpose: &str) -> Result<Option<credentials::Secret>, credentials::CredentialError> {
+    let Some(name) = load_engine_registry().get(engine_id).credentials.get(purpose).cloned() else {
+        return Ok(None);
+    };
+    let root = app_root().map_err(|e| credentials::CredentialError::Backend(format!("{:?}", e)))?;
+    credentials::CredentialStore::load(&root)?.resolve(&name).await.map(Some)
+}
+
+// -----------------------------
+// PYTHON BLAST ENGINE (NEW!)
+// -----------------------------
+#[async_trait::async_trait]
+impl BlastEngine for PythonBlastEngine {
+    fn name(&self) -> &'static str { "Python BLAST Engine" }
+
+    async fn execute(&self, request: BlastExecutionRequest) -> Result<BlastResult, BlastEngineError> {
+        log!("🐍 Python engine executing job {}", request.job_id);
+
+        let input_path = match request.input {
+            BlastInput::FilePath(ref path) => path,
+            _ => return Err(BlastEngineError::InvalidInput(
+                "Python engine requires file input".to_string()
+            )),
+        };
+
+        if !input_path.exists() {
+            return Err(BlastEngineError::InvalidInput(
+                format!("Input file does not exist: {:?}", input_path)
+            ));
+        }
+
+        let output_dir = &request.output_dir;
+        fs::create_dir_all(output_dir).await
+            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Cannot create output dir: {}", e)))?;
+        
+        let output_path = output_dir.join(format!("python_blast_{}.xml", request.job_id));
+
+        log!("📄 Input: {:?}", input_path);
+        log!("💾 Output: {:?}", output_path);
+
+        // Use curl to call the Flask API
+        let blast_type = request.blast_type.to_string();
+        
+        let output = Command::new("curl")
+            .arg("-X")
+            .arg("POST")
+            .arg("-F")
+            .arg(format!("file=@{}", input_path.display()))
+            .arg("-F")
+            .arg(format!("blastType={}", blast_type))
+            .arg("http://127.0.0.1:5001/run_blast")
+            .arg("-o")
+            .arg(&output_path)
+            .arg("-sS")
+            .arg("-w")
+            .arg("%{http_code}")
+            .output()
+            .await
+            .map_err(|e| BlastEngineError::ExecutionFailed(
+                format!("Failed to call Python API: {}", e)
+            ))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(BlastEngineError::ExecutionFailed(
+                format!("Python API call failed: {}", stderr)
+            ));
+        }
+
+        // With -w, curl prints only the HTTP status; the body went to the output file
+        let status: u16 = String::from_utf8_lossy(&output.stdout).trim().parse().unwrap_or(0);
+        if governor::is_throttling_status(status) {
+            return Err(BlastEngineError::Throttled(format!("HTTP {}", status)));
+        }
+        if status >= 400 {
+            let body = fs::read_to_string(&output_path).await.unwrap_or_default();
+            return Err(BlastEngineError::ExecutionFailed(
+                format!("Python API returned HTTP {}: {}", status, body.trim())
+            ));
+        }
+
+        log!("✅ Python BLAST completed successfully");
+
+        Ok(BlastResult {
+            job_id: request.job_id,
+            status: ResultStatus::Success,
+            output: ResultOutput::FilePath(output_path),
+        })
+    }
+}
+
+// -----------------------------
+// RUST PROCESS ENGINE
+// -----------------------------
+#[async_trait::async_trait]
+impl BlastEngine for RustProcessEngine {
+    fn name(&self) -> &'static str { "RUST engine" }
+
+    async fn execute(&self, request: BlastExecutionRequest) -> Result<BlastResult, BlastEngineError> {
+        log!("🦀 RUST engine executing job {}", request.job_id);
+
+        let input_path = match request.input {
+            BlastInput::FilePath(ref path) => path,
+            _ => return Err(BlastEngineError::InvalidInput(
+                "RUST engine requires file input".to_string()
+            )),
+        };
+
+        // Get app root for the engine sources
+        let app_root = app_root()?;
+        
+        let output_dir = &request.output_dir;
+        fs::create_dir_all(output_dir).await
+            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Cannot create output dir: {}", e)))?;
+        
+        let file_name = format!("rust_engine_{}.txt", request.job_id);
+        let output_path = if request.compress_output {
+            compression::gzip_path(&output_dir.join(file_name))
+        } else {
+            output_dir.join(file_name)
+        };
+        let engine_dir = app_root.join("engines").join("rust_engine");
+
+        let mut child = Command::new("cargo")
+            .args(["run", "--quiet", "--"])
+            .arg(request.job_id.to_string())
+            .arg(input_path)
+            .current_dir(&engine_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Spawn failed: {}", e)))?;
+
+        // Results go straight to disk; stderr is logged as it arrives
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+        let stderr_log = tokio::spawn(streaming::log_lines(stderr, format!("job {} stderr", request.job_id)));
+
+        let streamed = streaming::stream_to_file(stdout, &output_path, request.job_id, request.compress_output).await;
+        let status = child.wait().await
+            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Wait failed: {}", e)))?;
+        let _ = stderr_log.await;
+
+        if let Err(e) = streamed {
+            let _ = fs::remove_file(&output_path).await;
+            return Err(BlastEngineError::ExecutionFailed(format!("Write failed: {}", e)));
+        }
+        if !status.success() {
+            let _ = fs::remove_file(&output_path).await;
+            return Err(BlastEngineError::ExecutionFailed(format!("Engine failed: {}", status)));
+        }
+
+        Ok(BlastResult {
+            job_id: request.job_id,
+            status: ResultStatus::Success,
+            output: ResultOutput::FilePath(output_path),
+        })
+    }
+}
+
+
+// -----------------------------
+// SCHEDULER IMPLEMENTATION
+// -----------------------------
+
+// Gzips a finished result unless the engine already wrote it compressed
+async fn compress_result(output_path: &std::path::Path) -> Result<PathBuf, String> {
+    if compression::is_gzip_file(output_path).await.map_err(|e| e.to_string())? {
+        return Ok(output_path.to_path_buf());
+    }
+    compression::compress_file(output_path).await
+}
+
+// Engine ids, as used in engines.json
+const ENGINE_IDS: [&str; 4] = ["rust", "python", "diamond", "hmmer"];
+
+impl Scheduler {
+    pub fn new(jobs: Vec<Job>) -> Self {
+        let root = app_root().unwrap_or_else(|_| PathBuf::from("."));
+        Self::with_root(jobs, root)
+    }
+
+    /// Uses `root` instead of application_root/ for outputs/ and registry files.
+    /// Registry and history problems are warned about on stderr, keeping
+    /// stdout clean for commands that print JSON.
+    pub fn with_root(jobs: Vec<Job>, root: PathBuf) -> Self {
+        let databases = databases::DatabaseRegistry::load(&root.join("databases.json"))
+            .unwrap_or_else(|err| {
+                log_error!("⚠️ Database registry not loaded: {}", err);
+                databases::DatabaseRegistry::default()
+            });
+
+        let engine_registry = engines::EngineRegistry::load(&root.join("engines.json"))
+            .unwrap_or_else(|err| {
+                log_error!("⚠️ Engine registry not loaded, using defaults: {}", err);
+                engines::EngineRegistry::default()
+            });
+
+        let history = history::load(&history::history_path(&root))
+            .unwrap_or_else(|err| {
+                log_error!("⚠️ Job history not loaded, runtimes won't be estimated: {}", err);
+                Vec::new()
+            });
+        let estimator = estimator::RuntimeEstimator::from_history(&history);
+
+        let rust_engine: Arc<dyn BlastEngine + Send + Sync> = Arc::new(RustProcessEngine);
+        let python_engine: Arc<dyn BlastEngine + Send + Sync> = Arc::new(PythonBlastEngine);
+        let diamond_engine: Arc<dyn BlastEngine + Send + Sync> = Arc::new(diamond::DiamondEngine);
+        let hmmer_engine: Arc<dyn BlastEngine + Send + Sync> = Arc::new(hmmer::HmmerEngine);
+
+        let mut governors = HashMap::new();
+        let mut compressed_engines = HashSet::new();
+        for id in ENGINE_IDS {
+            let config = engine_registry.get(id);
+            if let Some(limits) = config.rate_limit {
+                let governor = governor::Governor::new(limits).shared(governor::state_path(&root, id));
+                governors.insert(id, Arc::new(governor));
+            }
+            if config.compress_output {
+                compressed_engines.insert(id);
+            }
+        }
+
+        Self {
+            queue: jobs,
+            join_handle: vec![],
+            root,
+            rust_engine,
+            python_engine,
+            diamond_engine,
+            hmmer_engine,
+            databases,
+            governors,
+            compressed_engines,
+            policy: planning::QueuePolicy::default(),
+            history,
+            estimator,
+        }
+    }
+
+    pub fn with_policy(mut self, policy: planning::QueuePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Orders the queue by the scheduler's policy and estimates each job's
+    /// runtime and completion time. `run` dispatches in this order, which
+    /// only affects engines with a governor (see `planning`).
+    pub async fn plan(&mut self) -> Vec<planning::QueuedJob> {
+        let mut sizes = Vec::new();
+        let mut keys = Vec::new();
+        for job in &self.queue {
+            let size = estimator::query_size(&job.input_path).await.unwrap_or_default();
+            let estimate = self.estimator.estimate(job.program.to_string(), &job.database, size);
+            let owner = job.metadata.get(planning::OWNER_KEY).cloned().unwrap_or_default();
+            sizes.push((size, estimate));
+            keys.push((owner, estimate.map(|e| e.runtime)));
+        }
+
+        let order = planning::order(self.policy, &keys, &self.history);
+        let limits = self.governors
+            .iter()
+            .map(|(id, g)| (id.to_string(), (g.max_concurrent(), g.interval())))
+            .collect();
+        let mut eta = planning::EtaPlanner::new(limits);
+
+        let mut queue: Vec<Option<Job>> = std::mem::take(&mut self.queue).into_iter().map(Some).collect();
+        let mut planned = Vec::new();
+        for (position, index) in order.into_iter().enumerate() {
+            let job = queue[index].take().expect("order visits each job once");
+            let (size, estimate) = sizes[index];
+            let engine_id = self.engine_for(&job);
+            let engine = self.engine(engine_id).name();
+            planned.push(planning::QueuedJob {
+                position: position + 1,
+                job_id: job.id,
+                name: job.name.clone(),
+                program: job.program.to_string().to_owned(),
+                database: job.database.clone(),
+                engine: engine.to_string(),
+                owner: keys[index].0.clone(),
+                query_sequences: size.sequences,
+                query_residues: size.residues,
+                estimated_secs: estimate.map(|e| e.runtime.as_secs_f64()),
+                eta_secs: eta.schedule(engine_id, keys[index].1).map(|d| d.as_secs_f64()),
+                samples: estimate.map_or(0, |e| e.samples),
+            });
+            self.queue.push(job);
+        }
+        planned
+    }
+
+    // Profile searches go to HMMER and protein searches against a local
+    // database go to DIAMOND; everything else is sent to the remote Python engine
+    fn engine_for(&self, job: &Job) -> &'static str {
+        if matches!(job.program, BlastType::HmmScan | BlastType::HmmSearch) {
+            return "hmmer";
+        }
+
+        let database = self.databases.resolve(&job.database);
+        let protein = matches!(job.program, BlastType::BlastP | BlastType::BlastX);
+        let local_db = PathBuf::from(&database).exists()
+            || PathBuf::from(format!("{}.dmnd", database)).exists();
+        let protein_db = matches!(
+            self.databases.kind_of(&job.database),
+            None | Some(databases::DatabaseKind::Protein)
+        );
+        if protein && local_db && protein_db {
+            "diamond"
+        } else {
+            "python"
+        }
+    }
+
+    fn engine(&self, id: &str) -> Arc<dyn BlastEngine + Send + Sync> {
+        match id {
+            "rust" => Arc::clone(&self.rust_engine),
+            "diamond" => Arc::clone(&self.diamond_engine),
+            "hmmer" => Arc::clone(&self.hmmer_engine),
+            _ => Arc::clone(&self.python_engine),
+        }
+    }
+
+    /// Runs every job on `engine` instead. Jobs are still routed to an engine
+    /// id as usual, and that id's engines.json settings (rate limit,
+    /// compression) apply to `engine`.
+    pub fn with_engine(mut self, engine: Arc<dyn BlastEngine + Send + Sync>) -> Self {
+        self.rust_engine = Arc::clone(&engine);
+        self.python_engine = Arc::clone(&engine);
+        self.diamond_engine = Arc::clone(&engine);
+        self.hmmer_engine = engine;
+        self
+    }
+
+    pub async fn run(mut self) -> Vec<JobOutcome> {
+        log!("Scheduler started");
+
+        let planned = self.plan().await;
+        let history_path = history::history_path(&self.root);
+
+        for (job, planned) in std::mem::take(&mut self.queue).into_iter().zip(planned) {
+            log!("Dispatching job {}", job.id);
+            if let (Some(estimate), Some(eta)) = (planned.estimated_secs, planned.eta_secs) {
+                log!("Job {} estimated to take {:.0}s, ETA {:.0}s", job.id, estimate, eta);
+            }
+
+            let engine_id = self.engine_for(&job);
+            let engine = self.engine(engine_id);
+
+            log!("Job {} assigned to engine: {}", job.id, engine.name());
+
+            let compress_output = job.compress_output
+                .unwrap_or_else(|| self.compressed_engines.contains(engine_id));
+
+            let request = BlastExecutionRequest {
+                job_id: job.id as u64,
+                blast_type: job.program.clone(),
+                database: self.databases.resolve(&job.database),
+                database_kind: self.databases.kind_of(&job.database),
+                input: BlastInput::FilePath(job.input_path.clone()),
+                parameters: BlastParameters,
+                output_dir: self.root.join("outputs"),
+                compress_output,
+            };
+
+            let mut job_metadata = metadata::JobMetadata {
+                job_id: job.id as u64,
+                name: job.name.clone(),
+                program: job.program.to_string().to_owned(),
+                database: job.database.clone(),
+                engine: engine.name().to_string(),
+                input_path: job.input_path.clone(),
+                output_path: PathBuf::new(),
+                compressed: false,
+                extra: job.metadata.clone(),
+            };
+
+            let governor = self.governors.get(engine_id).cloned();
+            // Taken here, in plan order, so the governor serves jobs in that order
+            let ticket = governor.as_ref().map(|governor| governor.ticket());
+            let history_path = history_path.clone();
+
+            let handle = tokio::spawn(async move {
+                let (mut result, elapsed) =
+                    governor::execute_timed(engine.as_ref(), request, governor.as_deref().zip(ticket)).await;
+
+                let entry = history::HistoryEntry {
+                    job_id: job.id as u64,
+                    program: planned.program,
+                    database: planned.database,
+                    engine: planned.engine,
+                    owner: planned.owner,
+                    query_sequences: planned.query_sequences,
+                    query_residues: planned.query_residues,
+                    duration_secs: elapsed.as_secs_f64(),
+                    succeeded: result.is_ok(),
+                    finished_at: history::HistoryEntry::now(),
+                };
+                if let Err(err) = history::append(&history_path, &entry).await {
+                    log!("Job {} not recorded in history: {}", job.id, err);
+                }
+
+                if compress_output {
+                    if let Ok(finished) = &mut result {
+                        let ResultOutput::FilePath(output_path) = &mut finished.output;
+                        match compress_result(output_path).await {
+                            Ok(compressed) => *output_path = compressed,
+                            Err(err) => log!("Job {} output left uncompressed: {}", job.id, err),
+                        }
+                    }
+                }
+                match &result {
+                    Ok(result) => {
+                        log!("Job {} completed successfully. Output: {:?}", result.job_id, result.output);
+                        let ResultOutput::FilePath(output_path) = &result.output;
+                        job_metadata.output_path = output_path.clone();
+                        job_metadata.compressed = compression::is_gzip_file(output_path).await.unwrap_or(false);
+                        if let Err(err) = metadata::write_sidecar(&job_metadata).await {
+                            log!("Job {} metadata not written: {}", job.id, err);
+                        }
+                    }
+                    Err(err) => log!("Job {} failed: {:?}", job.id, err),
+                }
+                JobOutcome { job_id: job.id, result }
+            });
+
+            self.join_handle.push(handle);
+        }
+
+        log!("Scheduler finished dispatching jobs");
+
+        let mut outcomes = Vec::new();
+        for handle in self.join_handle {
+            if let Ok(outcome) = handle.await {
+                outcomes.push(outcome);
+            }
+        }
+
+        log!("All jobs completed");
+        outcomes
+    }
+}
@@ -1,346 +1,428 @@
 // Standard library imports
 use std::path::PathBuf;
-use std::sync::Arc;
 use std::env;
 use tokio::fs;
-use tokio::process::Command;
+
+use scheduler::{compression, diff, engine_credential, features, governor, ncbi, planning, results, visualization};
+use scheduler::{app_root, load_engine_registry, log, log_error, BlastType, Job, Scheduler};
 
 // -----------------------------
-// Structs
+// MAIN ENTRY
 // -----------------------------
-struct Job {
-    id: u32,
-    name: String,
-    schedule: std::time::Duration,
-    state: JobState,
-    input_path: PathBuf,
-    database: String,
-    output_path: PathBuf,
-    program: BlastType,
-}
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().collect();
 
-struct BlastExecutionRequest {
-    job_id: u64,
-    blast_type: BlastType,
-    input: BlastInput,
-    parameters: BlastParameters,
+    // Subcommands used by the UI; anything else is treated as a FASTA path
+    match args.get(1).map(String::as_str) {
+        Some("results") => results_command(&args[2..]).await,
+        Some("diff") => diff_command(&args[2..]).await,
+        Some("export") => export_command(&args[2..]).await,
+        Some("accessions") => accessions_command(&args[2..]).await,
+        Some("download") => download_command(&args[2..]).await,
+        Some("list") => list_command(&args[2..]).await,
+        _ => run_blast_job(&args).await,
+    }
 }
 
-struct RustProcessEngine;
-struct PythonBlastEngine;
-
-struct BlastParameters;
+// Prints the visualization JSON for a BLAST XML result to stdout
+async fn results_command(args: &[String]) {
+    let Some(result_path) = args.first().map(PathBuf::from) else {
+        log_error!("Error: No result file provided");
+        log_error!("Usage: scheduler results <path_to_blast_xml>");
+        std::process::exit(1);
+    };
 
-struct Scheduler {
-    queue: Vec<Job>,
-    join_handle: Vec<tokio::task::JoinHandle<()>>,
-    rust_engine: Arc<dyn BlastEngine + Send + Sync>,
-    python_engine: Arc<dyn BlastEngine + Send + Sync>,
-}
+    let report = match results::load_report(&result_path).await {
+        Ok(report) => report,
+        Err(err) => {
+            log_error!("Error: Cannot parse results: {:?}", err);
+            std::process::exit(1);
+        }
+    };
 
-struct BlastResult {
-    job_id: u64,
-    status: ResultStatus,
-    output: ResultOutput,
+    println!("{}", visualization::VisualizationReport::from_report(&report).to_json());
 }
 
-// -----------------------------
-// Enums
-// -----------------------------
-
-enum JobState { 
-    Queued, 
-    Running, 
-    Completed 
-}
+// Compares two result files and prints gained/lost/changed hits
+async fn diff_command(args: &[String]) {
+    const USAGE: &str = "Usage: scheduler diff <baseline_xml> <candidate_xml> [--json] \
+        [--max-evalue <e>] [--min-bitscore-delta <bits>] [--min-identity-delta <percent>]";
+
+    let mut paths = Vec::new();
+    let mut thresholds = diff::DiffThresholds::default();
+    let mut as_json = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let target = match arg.as_str() {
+            "--json" => {
+                as_json = true;
+                continue;
+            }
+            "--max-evalue" => &mut thresholds.max_evalue,
+            "--min-bitscore-delta" => &mut thresholds.min_bitscore_delta,
+            "--min-identity-delta" => &mut thresholds.min_identity_delta,
+            _ => {
+                paths.push(PathBuf::from(arg));
+                continue;
+            }
+        };
+        match iter.next().and_then(|value| value.parse().ok()) {
+            Some(value) => *target = value,
+            None => {
+                log_error!("Error: {} expects a number", arg);
+                log_error!("{}", USAGE);
+                std::process::exit(1);
+            }
+        }
+    }
 
-#[derive(Debug, Clone)]
-enum BlastType { 
-    BlastN, 
-    BlastP, 
-    BlastX, 
-    TBlastN,
-    TBlastX 
-}
+    let [baseline_path, candidate_path] = paths.as_slice() else {
+        log_error!("Error: Expected exactly two result files");
+        log_error!("{}", USAGE);
+        std::process::exit(1);
+    };
 
-impl BlastType {
-    fn to_string(&self) -> &str {
-        match self {
-            BlastType::BlastN => "blastn",
-            BlastType::BlastP => "blastp",
-            BlastType::BlastX => "blastx",
-            BlastType::TBlastN => "tblastn",
-            BlastType::TBlastX => "tblastx",
+    let mut reports = Vec::new();
+    for path in [baseline_path, candidate_path] {
+        match results::load_report(path).await {
+            Ok(report) => reports.push(report),
+            Err(err) => {
+                log_error!("Error: Cannot parse results {:?}: {:?}", path, err);
+                std::process::exit(1);
+            }
         }
     }
-}
 
-#[derive(Debug)]
-enum BlastInput { 
-    FilePath(PathBuf), 
-    RawBytes(Vec<u8>) 
+    let run_diff = diff::diff_reports(&reports[0], &reports[1], &thresholds);
+    if as_json {
+        println!("{}", run_diff.to_json());
+    } else {
+        print!("{}", run_diff.to_table());
+    }
 }
 
-enum ResultStatus { 
-    Success,
-    Failed 
-}
+// Writes hit locations as BED or GFF3 for genome browsers
+async fn export_command(args: &[String]) {
+    const USAGE: &str = "Usage: scheduler export <path_to_blast_xml> [--format bed|gff3] \
+        [--score bitscore|evalue|identity] [--merge] [--output <path>]";
+
+    let mut result_path = None;
+    let mut output_path = None;
+    let mut options = features::FeatureOptions {
+        format: features::FeatureFormat::Bed,
+        score: features::ScoreMapping::Bitscore,
+        merge: false,
+    };
 
-#[derive(Debug)]
-enum ResultOutput { 
-    FilePath(PathBuf) 
-}
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let parsed = match arg.as_str() {
+            "--merge" => {
+                options.merge = true;
+                Some(())
+            }
+            "--format" => iter.next()
+                .and_then(|v| features::FeatureFormat::from_name(v))
+                .map(|format| options.format = format),
+            "--score" => iter.next()
+                .and_then(|v| features::ScoreMapping::from_name(v))
+                .map(|score| options.score = score),
+            "--output" => iter.next().map(|v| output_path = Some(PathBuf::from(v))),
+            _ => {
+                result_path = Some(PathBuf::from(arg));
+                Some(())
+            }
+        };
+        if parsed.is_none() {
+            log_error!("Error: Invalid value for {}", arg);
+            log_error!("{}", USAGE);
+            std::process::exit(1);
+        }
+    }
 
-#[derive(Debug)]
-enum BlastEngineError {
-    InvalidInput(String),
-    UnsupportedFormat,
-    DatabaseUnavailable,
-    ExecutionFailed(String),
-    Timeout,
-}
+    let Some(result_path) = result_path else {
+        log_error!("Error: No result file provided");
+        log_error!("{}", USAGE);
+        std::process::exit(1);
+    };
 
-// -----------------------------
-// Traits 
-// -----------------------------
-#[async_trait::async_trait]
-trait BlastEngine {
-    async fn execute(&self, request: BlastExecutionRequest) -> Result<BlastResult, BlastEngineError>;
-    fn name(&self) -> &'static str;
+    let report = match results::load_report(&result_path).await {
+        Ok(report) => report,
+        Err(err) => {
+            log_error!("Error: Cannot parse results: {:?}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let contents = match features::export_features(&report, &options) {
+        Ok(contents) => contents,
+        Err(err) => {
+            log_error!("Error: {}", err);
+            std::process::exit(1);
+        }
+    };
+    match output_path {
+        Some(path) => {
+            if let Err(err) = fs::write(&path, contents).await {
+                log_error!("Error: Cannot write {:?}: {}", path, err);
+                std::process::exit(1);
+            }
+            log!("Features written to {:?}", path);
+        }
+        None => print!("{}", contents),
+    }
 }
 
-// -----------------------------
-// PYTHON BLAST ENGINE (NEW!)
-// -----------------------------
-#[async_trait::async_trait]
-impl BlastEngine for PythonBlastEngine {
-    fn name(&self) -> &'static str { "Python BLAST Engine" }
-
-    async fn execute(&self, request: BlastExecutionRequest) -> Result<BlastResult, BlastEngineError> {
-        println!("🐍 Python engine executing job {}", request.job_id);
-
-        let input_path = match request.input {
-            BlastInput::FilePath(ref path) => path,
-            _ => return Err(BlastEngineError::InvalidInput(
-                "Python engine requires file input".to_string()
-            )),
-        };
+// Copies a result file to where the user saved it; a .gz destination gets
+// the result gzipped, anything else gets plain text
+async fn download_command(args: &[String]) {
+    const USAGE: &str = "Usage: scheduler download <result_path> <destination>";
 
-        if !input_path.exists() {
-            return Err(BlastEngineError::InvalidInput(
-                format!("Input file does not exist: {:?}", input_path)
-            ));
-        }
+    let [result_path, destination] = args else {
+        log_error!("Error: Expected a result file and a destination");
+        log_error!("{}", USAGE);
+        std::process::exit(1);
+    };
+    let (result_path, destination) = (PathBuf::from(result_path), PathBuf::from(destination));
 
-        // Get app root and build paths
-        let exe_path = env::current_exe()
-            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Cannot get exe path: {}", e)))?;
-        let app_root = exe_path.parent()
-            .and_then(|p| p.parent())
-            .and_then(|p| p.parent())
-            .ok_or(BlastEngineError::ExecutionFailed("Cannot determine app root".to_string()))?;
-        
-        let output_dir = app_root.join("outputs");
-        fs::create_dir_all(&output_dir).await
-            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Cannot create output dir: {}", e)))?;
-        
-        let output_path = output_dir.join(format!("python_blast_{}.xml", request.job_id));
-
-        println!("📄 Input: {:?}", input_path);
-        println!("💾 Output: {:?}", output_path);
-
-        // Find the Python Flask server (assuming it's in application_root/python_engine/)
-        let python_dir = app_root.join("python_engine");
-        
-        // Use curl to call the Flask API
-        let blast_type = request.blast_type.to_string();
-        
-        let output = Command::new("curl")
-            .arg("-X")
-            .arg("POST")
-            .arg("-F")
-            .arg(format!("file=@{}", input_path.display()))
-            .arg("-F")
-            .arg(format!("blastType={}", blast_type))
-            .arg("http://127.0.0.1:5001/run_blast")
-            .arg("-o")
-            .arg(&output_path)
-            .output()
-            .await
-            .map_err(|e| BlastEngineError::ExecutionFailed(
-                format!("Failed to call Python API: {}", e)
-            ))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(BlastEngineError::ExecutionFailed(
-                format!("Python API call failed: {}", stderr)
-            ));
-        }
+    let want_compressed = destination.extension().is_some_and(|ext| ext == "gz");
 
-        println!("✅ Python BLAST completed successfully");
+    let saved = compression::copy_result(&result_path, &destination, want_compressed).await;
 
-        Ok(BlastResult {
-            job_id: request.job_id,
-            status: ResultStatus::Success,
-            output: ResultOutput::FilePath(output_path),
-        })
+    if let Err(err) = saved {
+        log_error!("Error: {}", err);
+        std::process::exit(1);
     }
+    log!("Result saved to {:?}", destination);
 }
 
-// -----------------------------
-// RUST PROCESS ENGINE
-// -----------------------------
-#[async_trait::async_trait]
-impl BlastEngine for RustProcessEngine {
-    fn name(&self) -> &'static str { "RUST engine" }
-
-    async fn execute(&self, request: BlastExecutionRequest) -> Result<BlastResult, BlastEngineError> {
-        println!("🦀 RUST engine executing job {}", request.job_id);
-
-        let input_path = match request.input {
-            BlastInput::FilePath(ref path) => path,
-            _ => return Err(BlastEngineError::InvalidInput(
-                "RUST engine requires file input".to_string()
-            )),
+// Shows the order a set of queries would run in, with estimated runtimes
+// and completion times from past jobs
+async fn list_command(args: &[String]) {
+    const USAGE: &str = "Usage: scheduler list <fasta>... [--program <blast_type>] [--db <database>] \
+        [--policy fifo|sjf|fair] [--owner <name>] [--json]";
+
+    let mut input_paths = Vec::new();
+    let mut program = None;
+    let mut database = None;
+    let mut owner = None;
+    let mut policy = planning::QueuePolicy::default();
+    let mut as_json = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let parsed = match arg.as_str() {
+            "--program" => iter.next().and_then(|v| BlastType::from_name(v)).map(|p| program = Some(p)),
+            "--db" => iter.next().map(|v| database = Some(v.clone())),
+            "--owner" => iter.next().map(|v| owner = Some(v.clone())),
+            "--policy" => iter.next().and_then(|v| planning::QueuePolicy::from_name(v)).map(|p| policy = p),
+            "--json" => {
+                as_json = true;
+                Some(())
+            }
+            _ => {
+                input_paths.push(PathBuf::from(arg));
+                Some(())
+            }
         };
-
-        // Get app root and build output path
-        let exe_path = env::current_exe()
-            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Cannot get exe path: {}", e)))?;
-        let app_root = exe_path.parent()
-            .and_then(|p| p.parent())
-            .and_then(|p| p.parent())
-            .ok_or(BlastEngineError::ExecutionFailed("Cannot determine app root".to_string()))?;
-        
-        let output_dir = app_root.join("outputs");
-        fs::create_dir_all(&output_dir).await
-            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Cannot create output dir: {}", e)))?;
-        
-        let output_path = output_dir.join(format!("rust_engine_{}.txt", request.job_id));
-        let engine_dir = app_root.join("engines").join("rust_engine");
-
-        let output = Command::new("cargo")
-            .args(["run", "--quiet", "--"])
-            .arg(request.job_id.to_string())
-            .arg(input_path)
-            .current_dir(&engine_dir)
-            .output()
-            .await
-            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Spawn failed: {}", e)))?;
-
-        println!("--- Engine stdout ---\n{}", String::from_utf8_lossy(&output.stdout));
-        println!("--- Engine stderr ---\n{}", String::from_utf8_lossy(&output.stderr));
-
-        if !output.status.success() {
-            return Err(BlastEngineError::ExecutionFailed("Engine failed".to_string()));
+        if parsed.is_none() {
+            log_error!("Error: Invalid value for {}", arg);
+            log_error!("{}", USAGE);
+            std::process::exit(1);
         }
+    }
 
-        fs::write(&output_path, &output.stdout)
-            .await
-            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Write failed: {}", e)))?;
-
-        Ok(BlastResult {
-            job_id: request.job_id,
-            status: ResultStatus::Success,
-            output: ResultOutput::FilePath(output_path),
-        })
+    if input_paths.is_empty() {
+        log_error!("Error: No input files provided");
+        log_error!("{}", USAGE);
+        std::process::exit(1);
     }
-}
 
+    let jobs = queue_jobs(input_paths, &program, &database, &owner, None);
 
-// -----------------------------
-// SCHEDULER IMPLEMENTATION
-// -----------------------------
+    let planned = Scheduler::new(jobs).with_policy(policy).plan().await;
 
-impl Scheduler {
-    fn new(jobs: Vec<Job>) -> Self {
-        Self {
-            queue: jobs,
-            join_handle: vec![],
-            rust_engine: Arc::new(RustProcessEngine),
-            python_engine: Arc::new(PythonBlastEngine),
+    if as_json {
+        match serde_json::to_string_pretty(&planned) {
+            Ok(json) => println!("{}", json),
+            Err(err) => {
+                log_error!("Error: Cannot serialize queue: {}", err);
+                std::process::exit(1);
+            }
         }
+        return;
     }
 
-    async fn run(mut self) {
-        println!("Scheduler started");
-
-        while let Some(job) = self.queue.pop() {
-            println!("Dispatching job {}", job.id);
-
-            let request = BlastExecutionRequest {
-                job_id: job.id as u64,
-                blast_type: job.program.clone(),
-                input: BlastInput::FilePath(job.input_path.clone()),
-                parameters: BlastParameters,
-            };
-
-            // Use Python engine for all jobs
-            let engine = Arc::clone(&self.python_engine);
+    let secs = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.0}s", v));
+    println!("{:<4} {:<5} {:<10} {:<14} {:<20} {:>10} {:>9} {:>9}  name",
+        "#", "job", "program", "database", "engine", "residues", "estimate", "eta");
+    for job in &planned {
+        println!("{:<4} {:<5} {:<10} {:<14} {:<20} {:>10} {:>9} {:>9}  {}",
+            job.position, job.job_id, job.program, job.database, job.engine,
+            job.query_residues, secs(job.estimated_secs), secs(job.eta_secs), job.name);
+    }
+}
 
-            println!("Job {} assigned to engine: {}", job.id, engine.name());
+// One job per input file, numbered from 1 in the order given
+fn queue_jobs(
+    input_paths: Vec<PathBuf>,
+    program: &Option<BlastType>,
+    database: &Option<String>,
+    owner: &Option<String>,
+    compress_output: Option<bool>,
+) -> Vec<Job> {
+    input_paths
+        .into_iter()
+        .enumerate()
+        .map(|(index, input_path)| {
+            let mut job = Job::new(index as u32 + 1, input_path);
+            if let Some(program) = program {
+                job.program = program.clone();
+            }
+            if let Some(database) = database {
+                job.database = database.clone();
+            }
+            if let Some(owner) = owner {
+                job.metadata.insert(planning::OWNER_KEY.to_string(), owner.clone());
+            }
+            job.compress_output = compress_output;
+            job
+        })
+        .collect()
+}
 
-            let handle = tokio::spawn(async move {
-                match engine.execute(request).await {
-                    Ok(result) => println!("Job {} completed successfully. Output: {:?}", result.job_id, result.output),
-                    Err(err) => println!("Job {} failed: {:?}", job.id, err),
+// Fetches query sequences by accession, then runs them as a normal job
+async fn accessions_command(args: &[String]) {
+    const USAGE: &str = "Usage: scheduler accessions <accession_list_or_file> [--db nuccore|protein]";
+
+    let mut list = None;
+    let mut config = ncbi::EfetchConfig::default();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--db" => match iter.next().map(String::as_str) {
+                Some(db @ ("nuccore" | "protein")) => config.database = db.to_string(),
+                _ => {
+                    log_error!("Error: --db expects nuccore or protein");
+                    log_error!("{}", USAGE);
+                    std::process::exit(1);
                 }
-            });
+            },
+            _ => list = Some(arg.clone()),
+        }
+    }
 
-            self.join_handle.push(handle);
+    let Some(list) = list else {
+        log_error!("Error: No accessions provided");
+        log_error!("{}", USAGE);
+        std::process::exit(1);
+    };
+
+    // Same "ncbi" engine entry as the credential below
+    config.rate_limit = load_engine_registry().get("ncbi").rate_limit;
+    config.governor_state = app_root().ok().map(|root| governor::state_path(&root, "ncbi"));
+
+    // A configured credential takes precedence over NCBI_API_KEY
+    match engine_credential("ncbi", "api_key").await {
+        Ok(Some(key)) => config.api_key = Some(key),
+        Ok(None) => {}
+        Err(err) => {
+            log_error!("Error: Cannot load NCBI API key: {:?}", err);
+            std::process::exit(1);
         }
+    }
 
-        println!("Scheduler finished dispatching jobs");
+    let accessions = match ncbi::read_accession_list(&list).await {
+        Ok(accessions) => accessions,
+        Err(err) => {
+            log_error!("Error: Invalid accession list: {:?}", err);
+            std::process::exit(1);
+        }
+    };
 
-        for handle in self.join_handle {
-            let _ = handle.await;
+    let input_path = match ncbi::write_temp_fasta(&accessions, &config).await {
+        Ok(path) => path,
+        Err(err) => {
+            log_error!("Error: Cannot fetch sequences: {:?}", err);
+            std::process::exit(1);
         }
+    };
+
+    log!("Fetched {} accession(s) into {:?}", accessions.len(), input_path);
 
-        println!("All jobs completed");
+    let mut job = Job::new(1, input_path.clone());
+    job.name = format!("BLAST Job for {} accession(s)", accessions.len());
+    // Protein accessions are searched against a protein database too
+    if config.database == "protein" {
+        job.program = BlastType::BlastP;
+        job.database = "nr".to_string();
     }
+    job.metadata.insert("accessions".to_string(), accessions.join(","));
+    job.metadata.insert("accession_db".to_string(), config.database.clone());
+
+    Scheduler::new(vec![job]).run().await;
+
+    // The FASTA only existed to feed the engine
+    let _ = fs::remove_file(&input_path).await;
 }
 
-// -----------------------------
-// MAIN ENTRY
-// -----------------------------
-#[tokio::main]
-async fn main() {
-    // Get input file path from command line argument (from Electron UI)
-    let args: Vec<String> = env::args().collect();
-    
-    let input_path = if args.len() > 1 {
-        PathBuf::from(&args[1])
-    } else {
-        eprintln!("Error: No input file provided");
-        eprintln!("Usage: scheduler <path_to_fasta_file>");
-        std::process::exit(1);
-    };
+// Runs one job per FASTA file. With several files, --policy decides the
+// order they reach rate-limited engines in (see `scheduler list`)
+async fn run_blast_job(args: &[String]) {
+    const USAGE: &str = "Usage: scheduler <fasta>... [--program <blast_type>] [--db <database>] \
+        [--compress | --no-compress] [--policy fifo|sjf|fair] [--owner <name>]";
+
+    // Input file paths from the command line (the Electron UI passes one)
+    let mut input_paths = Vec::new();
+    let mut program = None;
+    let mut database = None;
+    let mut compress_output = None;
+    let mut owner = None;
+    let mut policy = planning::QueuePolicy::default();
+
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let parsed = match arg.as_str() {
+            "--program" => iter.next().and_then(|v| BlastType::from_name(v)).map(|p| program = Some(p)),
+            "--db" => iter.next().map(|v| database = Some(v.clone())),
+            "--owner" => iter.next().map(|v| owner = Some(v.clone())),
+            "--policy" => iter.next().and_then(|v| planning::QueuePolicy::from_name(v)).map(|p| policy = p),
+            flag @ ("--compress" | "--no-compress") => {
+                compress_output = Some(flag == "--compress");
+                Some(())
+            }
+            _ => {
+                input_paths.push(PathBuf::from(arg));
+                Some(())
+            }
+        };
+        if parsed.is_none() {
+            log_error!("Error: Invalid value for {}", arg);
+            log_error!("{}", USAGE);
+            std::process::exit(1);
+        }
+    }
 
-    // Verify input file exists
-    if !input_path.exists() {
-        eprintln!("Error: Input file does not exist: {:?}", input_path);
+    if input_paths.is_empty() {
+        log_error!("Error: No input file provided");
+        log_error!("{}", USAGE);
         std::process::exit(1);
     }
 
-    println!("Received input file: {:?}", input_path);
-
-    // Create job from the provided input path
-    // UI provides: input_path
-    // Scheduler fills in: id, name, schedule, program, database, state, output_path
-    let jobs = vec![
-        Job {
-            id: 1,
-            name: format!("BLAST Job for {}", input_path.file_name().unwrap().to_string_lossy()),
-            schedule: std::time::Duration::from_secs(0),
-            program: BlastType::BlastN,  // Default to BlastN
-            database: "nt".to_string(),   // Default to nucleotide database
-            state: JobState::Queued,
-            input_path,
-            output_path: PathBuf::new(),  // Will be set by engine
+    // Verify input files exist
+    for input_path in &input_paths {
+        if !input_path.exists() {
+            log_error!("Error: Input file does not exist: {:?}", input_path);
+            std::process::exit(1);
         }
-    ];
+        log!("Received input file: {:?}", input_path);
+    }
+
+    let jobs = queue_jobs(input_paths, &program, &database, &owner, compress_output);
 
-    let scheduler = Scheduler::new(jobs);
+    let scheduler = Scheduler::new(jobs).with_policy(policy);
     scheduler.run().await;
-}
\ No newline at end of file
+}
@@ -0,0 +1,90 @@
+// -----------------------------
+// JOB METADATA
+// -----------------------------
+// Every finished job gets a `<result>.meta.json` sidecar describing where the
+// result came from, so the UI and later tooling don't have to guess.
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::credentials::redact;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobMetadata {
+    pub job_id: u64,
+    pub name: String,
+    pub program: String,
+    pub database: String,
+    pub engine: String,
+    pub input_path: PathBuf,
+    pub output_path: PathBuf,
+    /// Whether `output_path` is gzipped
+    pub compressed: bool,
+    /// Free-form job details, e.g. the accessions a query file was fetched from
+    pub extra: BTreeMap<String, String>,
+}
+
+pub fn sidecar_path(output_path: &Path) -> PathBuf {
+    let mut name = output_path.as_os_str().to_os_string();
+    name.push(".meta.json");
+    PathBuf::from(name)
+}
+
+impl JobMetadata {
+    // Field by field: in the serialized JSON a secret containing `"` or `\`
+    // no longer matches its escaped form
+    fn redacted(&self) -> Self {
+        let text = |value: &str| redact(value);
+        let path = |value: &Path| PathBuf::from(redact(&value.to_string_lossy()));
+        Self {
+            job_id: self.job_id,
+            name: text(&self.name),
+            program: text(&self.program),
+            database: text(&self.database),
+            engine: text(&self.engine),
+            input_path: path(&self.input_path),
+            output_path: path(&self.output_path),
+            compressed: self.compressed,
+            extra: self.extra.iter().map(|(k, v)| (text(k), text(v))).collect(),
+        }
+    }
+}
+
+pub async fn write_sidecar(metadata: &JobMetadata) -> Result<PathBuf, String> {
+    let path = sidecar_path(&metadata.output_path);
+    // Metadata is shared with the UI and outlives the job, so scrub credentials
+    let json = serde_json::to_string_pretty(&metadata.redacted())
+        .map_err(|e| format!("Cannot serialize metadata: {}", e))?;
+    fs::write(&path, json)
+        .await
+        .map_err(|e| format!("Cannot write {:?}: {}", path, e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::Secret;
+
+    #[test]
+    fn secrets_that_json_escapes_are_still_redacted() {
+        let secret = Secret::new("s3c\"re\\t".to_string());
+        let metadata = JobMetadata {
+            job_id: 1,
+            name: format!("query {}", secret.expose()),
+            program: "blastn".to_string(),
+            database: "nt".to_string(),
+            engine: "Rust engine".to_string(),
+            input_path: PathBuf::from("q.fasta"),
+            output_path: PathBuf::from(format!("out_{}.xml", secret.expose())),
+            compressed: false,
+            extra: BTreeMap::from([("api_key".to_string(), secret.expose().to_string())]),
+        };
+
+        let json = serde_json::to_string(&metadata.redacted()).unwrap();
+        assert!(!json.contains("s3c"), "{}", json);
+        assert!(json.contains("query ****"));
+        assert!(json.contains("out_****.xml"));
+    }
+}
@@ -0,0 +1,310 @@
+// -----------------------------
+// NCBI E-UTILITIES (efetch)
+// -----------------------------
+// Turns a list of GenBank/RefSeq accessions into a FASTA file so the job can
+// run exactly like an uploaded one.
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::process::Stdio;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::credentials::Secret;
+use crate::engines::RateLimitConfig;
+use crate::governor::{self, Governor};
+
+const EFETCH_URL: &str = "https://eutils.ncbi.nlm.nih.gov/entrez/eutils/efetch.fcgi";
+
+#[derive(Debug, Clone)]
+pub struct EfetchConfig {
+    /// Entrez database: "nuccore" or "protein"
+    pub database: String,
+    pub efetch_url: String,
+    pub api_key: Option<Secret>,
+    pub batch_size: usize,
+    pub max_retries: u32,
+    /// From the "ncbi" entry of engines.json; None uses NCBI's published limits
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Governor state file shared with other scheduler processes
+    pub governor_state: Option<PathBuf>,
+}
+
+impl Default for EfetchConfig {
+    fn default() -> Self {
+        Self {
+            database: "nuccore".to_string(),
+            efetch_url: EFETCH_URL.to_string(),
+            api_key: env::var("NCBI_API_KEY").ok().filter(|key| !key.is_empty()).map(Secret::new),
+            batch_size: 200,
+            max_retries: 3,
+            rate_limit: None,
+            governor_state: None,
+        }
+    }
+}
+
+impl EfetchConfig {
+    // NCBI allows 3 requests/sec without an API key and 10 with one
+    fn limits(&self) -> RateLimitConfig {
+        self.rate_limit.clone().unwrap_or(RateLimitConfig {
+            requests_per_sec: if self.api_key.is_some() { 10.0 } else { 3.0 },
+            max_concurrent: 1,
+            cooldown_secs: 10,
+            max_retries: self.max_retries,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum FetchError {
+    InvalidAccession(String),
+    RequestFailed(String),
+    /// NCBI answered 429/502/503/504 more often than the retry limit allows
+    Throttled(String),
+    MissingRecords(String),
+    Io(String),
+}
+
+/// Accepts a path to a file with one accession per line, or a comma/space
+/// separated list given directly on the command line. Anything after a `#`
+/// on a line is a comment.
+pub async fn read_accession_list(arg: &str) -> Result<Vec<String>, FetchError> {
+    let path = Path::new(arg);
+    let text = if path.is_file() {
+        fs::read_to_string(path)
+            .await
+            .map_err(|e| FetchError::Io(format!("Cannot read {:?}: {}", path, e)))?
+    } else {
+        arg.to_string()
+    };
+
+    let accessions: Vec<String> = text
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|line| line.split(|c: char| c == ',' || c.is_whitespace()))
+        .filter(|acc| !acc.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if accessions.is_empty() {
+        return Err(FetchError::InvalidAccession("No accessions provided".to_string()));
+    }
+    for acc in &accessions {
+        let valid = acc.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+        if !valid {
+            return Err(FetchError::InvalidAccession(acc.clone()));
+        }
+    }
+    Ok(accessions)
+}
+
+pub async fn fetch_fasta(accessions: &[String], config: &EfetchConfig) -> Result<String, FetchError> {
+    let mut fasta = String::new();
+    let mut governor = Governor::new(config.limits());
+    if let Some(path) = &config.governor_state {
+        governor = governor.shared(path.clone());
+    }
+
+    for (index, batch) in accessions.chunks(config.batch_size.max(1)).enumerate() {
+        crate::log!("🌐 Fetching {} accession(s) from NCBI ({} batch {})", batch.len(), config.database, index + 1);
+
+        let records = fetch_batch_governed(batch, config, &governor).await?;
+        let returned = records.lines().filter(|line| line.starts_with('>')).count();
+        if returned < batch.len() {
+            return Err(FetchError::MissingRecords(format!(
+                "NCBI returned {} of {} records for: {}",
+                returned,
+                batch.len(),
+                batch.join(",")
+            )));
+        }
+
+        fasta.push_str(records.trim_end());
+        fasta.push('\n');
+    }
+
+    Ok(fasta)
+}
+
+// Only throttling responses are retried; anything else will not get better
+async fn fetch_batch_governed(batch: &[String], config: &EfetchConfig, governor: &Governor) -> Result<String, FetchError> {
+    let mut attempt = 0;
+    loop {
+        let permit = governor.acquire().await;
+        let result = fetch_batch(batch, config).await;
+        drop(permit);
+
+        match result {
+            Err(FetchError::Throttled(reason)) if attempt < governor.max_retries() => {
+                attempt += 1;
+                crate::log!(
+                    "⏳ NCBI throttled efetch ({}), cooling down for {}s",
+                    reason,
+                    config.limits().cooldown_secs
+                );
+                governor.cool_down();
+            }
+            other => return other,
+        }
+    }
+}
+
+async fn fetch_batch(batch: &[String], config: &EfetchConfig) -> Result<String, FetchError> {
+    // POST keeps long ID lists out of the URL
+    let mut command = Command::new("curl");
+    command
+        .arg("-sS")
+        .arg("-w").arg("\n%{http_code}")
+        .arg("--data-urlencode").arg(format!("db={}", config.database))
+        .arg("--data-urlencode").arg(format!("id={}", batch.join(",")))
+        .arg("--data-urlencode").arg("rettype=fasta")
+        .arg("--data-urlencode").arg("retmode=text")
+        .arg("--data-urlencode").arg("tool=nucloflo");
+    // The key is fed through stdin so it never shows up in the process list
+    if config.api_key.is_some() {
+        command.arg("--data-urlencode").arg("api_key@-");
+    }
+
+    let mut child = command
+        .arg(&config.efetch_url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| FetchError::RequestFailed(format!("Failed to run curl: {}", e)))?;
+
+    let mut stdin = child.stdin.take().expect("curl stdin is piped");
+    if let Some(key) = &config.api_key {
+        stdin.write_all(key.expose().as_bytes())
+            .await
+            .map_err(|e| FetchError::RequestFailed(format!("Cannot pass API key to curl: {}", e)))?;
+    }
+    drop(stdin);
+
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| FetchError::RequestFailed(format!("Failed to run curl: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(FetchError::RequestFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string()
+        ));
+    }
+
+    // With -w, the HTTP status follows the body on its own line
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (body, status) = stdout.rsplit_once('\n').unwrap_or(("", stdout.as_ref()));
+    let status: u16 = status.trim().parse().unwrap_or(0);
+    if governor::is_throttling_status(status) {
+        return Err(FetchError::Throttled(format!("HTTP {}", status)));
+    }
+    if status >= 400 {
+        let snippet: String = body.trim().chars().take(200).collect();
+        return Err(FetchError::RequestFailed(format!("efetch returned HTTP {}: {}", status, snippet)));
+    }
+    // Errors come back as 200 with an error document instead of FASTA
+    if !body.trim_start().starts_with('>') {
+        let snippet: String = body.trim().chars().take(200).collect();
+        return Err(FetchError::RequestFailed(format!("Unexpected efetch response: {}", snippet)));
+    }
+    Ok(body.to_string())
+}
+
+/// Fetches the accessions and writes them to a new temporary FASTA file.
+pub async fn write_temp_fasta(accessions: &[String], config: &EfetchConfig) -> Result<PathBuf, FetchError> {
+    let fasta = fetch_fasta(accessions, config).await?;
+
+    // The temp dir is shared, so never reuse (or follow) a file already there
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or_default();
+    for attempt in 0..100u32 {
+        let path = env::temp_dir().join(format!(
+            "nucloflo_accessions_{}_{}_{}.fasta",
+            std::process::id(),
+            nanos,
+            attempt
+        ));
+        let mut file = match fs::OpenOptions::new().write(true).create_new(true).open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(FetchError::Io(format!("Cannot create {:?}: {}", path, e))),
+        };
+        file.write_all(fasta.as_bytes())
+            .await
+            .map_err(|e| FetchError::Io(format!("Cannot write {:?}: {}", path, e)))?;
+        return Ok(path);
+    }
+    Err(FetchError::Io("Cannot find a free temporary file name".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn comments_are_stripped_before_splitting() {
+        let accessions = read_accession_list("# my list\nNM_000546.6, NC_045512.2 # spike\n\nAB000001").await.unwrap();
+        assert_eq!(accessions, ["NM_000546.6", "NC_045512.2", "AB000001"]);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_accessions() {
+        assert!(matches!(read_accession_list("NM_1;rm").await, Err(FetchError::InvalidAccession(_))));
+        assert!(matches!(read_accession_list("# only a comment").await, Err(FetchError::InvalidAccession(_))));
+    }
+
+    // Answers each connection with the next canned HTTP response
+    async fn serve(responses: Vec<(u16, &'static str)>) -> (String, tokio::task::JoinHandle<usize>) {
+        use tokio::io::AsyncReadExt;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/efetch.fcgi", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut served = 0;
+            for (status, body) in responses {
+                let Ok((mut socket, _)) = listener.accept().await else { break };
+                let mut request = vec![0u8; 8192];
+                let _ = socket.read(&mut request).await;
+                let reply = format!(
+                    "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body
+                );
+                socket.write_all(reply.as_bytes()).await.unwrap();
+                served += 1;
+            }
+            served
+        });
+        (url, handle)
+    }
+
+    fn test_config(efetch_url: String) -> EfetchConfig {
+        EfetchConfig {
+            efetch_url,
+            api_key: None,
+            rate_limit: Some(RateLimitConfig {
+                requests_per_sec: 0.0,
+                max_concurrent: 1,
+                cooldown_secs: 0,
+                max_retries: 2,
+            }),
+            ..EfetchConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn throttled_batches_are_retried_through_the_governor() {
+        let (url, server) = serve(vec![(429, "slow down"), (200, ">NM_1 test\nACGT\n")]).await;
+        let fasta = fetch_fasta(&["NM_1".to_string()], &test_config(url)).await.unwrap();
+        assert_eq!(fasta, ">NM_1 test\nACGT\n");
+        assert_eq!(server.await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_not_retried() {
+        let (url, server) = serve(vec![(500, "boom"), (200, ">NM_1\nACGT\n")]).await;
+        let result = fetch_fasta(&["NM_1".to_string()], &test_config(url)).await;
+        assert!(matches!(result, Err(FetchError::RequestFailed(_))));
+        server.abort();
+    }
+}
@@ -0,0 +1,259 @@
+// -----------------------------
+// QUEUE PLANNING
+// -----------------------------
+// Orders the queue by policy and works out when each job should finish,
+// using the runtime estimator. ETAs assume each engine runs at most its
+// governor's max_concurrent jobs at once, spaced by its request interval.
+//
+// The order only matters for governed engines, which hand out their slots
+// strictly in dispatch order. Engines without a governor start every job at
+// once, so for them the policy changes nothing and a job's ETA is its own
+// estimate. Only jobs queued in one scheduler run are ordered: the UI starts
+// a process per job, so there the policy has nothing to reorder.
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::history::HistoryEntry;
+
+/// Job metadata key naming who a job belongs to, for fair-share.
+pub const OWNER_KEY: &str = "owner";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QueuePolicy {
+    /// Submission order
+    #[default]
+    Fifo,
+    /// Shortest estimated runtime first; jobs without an estimate go last
+    ShortestFirst,
+    /// Owner with the least past and planned engine time goes next
+    FairShare,
+}
+
+impl QueuePolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fifo" => Some(QueuePolicy::Fifo),
+            "sjf" => Some(QueuePolicy::ShortestFirst),
+            "fair" => Some(QueuePolicy::FairShare),
+            _ => None,
+        }
+    }
+}
+
+/// A queued job with its estimate, in dispatch order.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedJob {
+    pub position: usize,
+    pub job_id: u32,
+    pub name: String,
+    pub program: String,
+    pub database: String,
+    pub engine: String,
+    pub owner: String,
+    pub query_sequences: u64,
+    pub query_residues: u64,
+    /// Expected time in the engine
+    pub estimated_secs: Option<f64>,
+    /// Expected completion, in seconds from when the queue starts
+    pub eta_secs: Option<f64>,
+    /// Past runs the estimate is based on
+    pub samples: usize,
+}
+
+/// Dispatch order (indices into `jobs`) for `(owner, estimate)` pairs given
+/// in submission order.
+pub fn order(policy: QueuePolicy, jobs: &[(String, Option<Duration>)], history: &[HistoryEntry]) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..jobs.len()).collect();
+    match policy {
+        QueuePolicy::Fifo => {}
+        // Stable, so equal estimates keep submission order
+        QueuePolicy::ShortestFirst => indices.sort_by_key(|&i| (jobs[i].1.is_none(), jobs[i].1)),
+        QueuePolicy::FairShare => indices = fair_share(jobs, history),
+    }
+    indices
+}
+
+fn fair_share(jobs: &[(String, Option<Duration>)], history: &[HistoryEntry]) -> Vec<usize> {
+    let mut usage: HashMap<&str, f64> = HashMap::new();
+    for entry in history {
+        *usage.entry(entry.owner.as_str()).or_default() += entry.duration_secs;
+    }
+
+    // Jobs without an estimate are charged the average known one
+    let known: Vec<f64> = jobs.iter().filter_map(|(_, e)| e.map(|e| e.as_secs_f64())).collect();
+    let fallback = if known.is_empty() { 0.0 } else { known.iter().sum::<f64>() / known.len() as f64 };
+
+    // Each owner's jobs, in submission order; owners in order of first job
+    let mut owners: Vec<(&str, Vec<usize>)> = Vec::new();
+    for (index, (owner, _)) in jobs.iter().enumerate() {
+        match owners.iter_mut().find(|(o, _)| o == owner) {
+            Some((_, queue)) => queue.push(index),
+            None => owners.push((owner.as_str(), vec![index])),
+        }
+    }
+    for (_, queue) in owners.iter_mut() {
+        queue.reverse();
+    }
+
+    let mut ordered = Vec::with_capacity(jobs.len());
+    while ordered.len() < jobs.len() {
+        let (owner, queue) = owners
+            .iter_mut()
+            .filter(|(_, queue)| !queue.is_empty())
+            .min_by(|(a, _), (b, _)| {
+                let (a, b) = (usage.get(a).copied().unwrap_or(0.0), usage.get(b).copied().unwrap_or(0.0));
+                a.total_cmp(&b)
+            })
+            .expect("some owner still has jobs");
+        let index = queue.pop().expect("queue is not empty");
+        let charge = jobs[index].1.map_or(fallback, |e| e.as_secs_f64());
+        *usage.entry(owner).or_default() += charge;
+        ordered.push(index);
+    }
+    ordered
+}
+
+// Per-engine simulation of when each dispatched job finishes
+struct Lanes {
+    // Times at which each slot frees up; None once a job with no estimate ran in it
+    slots: Vec<Option<Duration>>,
+    interval: Duration,
+    next_start: Duration,
+}
+
+#[derive(Default)]
+pub struct EtaPlanner {
+    lanes: HashMap<String, Lanes>,
+}
+
+impl EtaPlanner {
+    /// `limits` is `(max_concurrent, request interval)` for governed engines.
+    pub fn new(limits: HashMap<String, (usize, Duration)>) -> Self {
+        let lanes = limits
+            .into_iter()
+            .map(|(engine, (slots, interval))| {
+                (engine, Lanes { slots: vec![Some(Duration::ZERO); slots.max(1)], interval, next_start: Duration::ZERO })
+            })
+            .collect();
+        Self { lanes }
+    }
+
+    /// Completion time of the next job dispatched to `engine`.
+    pub fn schedule(&mut self, engine: &str, estimate: Option<Duration>) -> Option<Duration> {
+        let Some(lanes) = self.lanes.get_mut(engine) else {
+            return estimate;
+        };
+
+        // Earliest-free slot; unknown slots are never picked before known ones
+        let slot = lanes
+            .slots
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, free)| (free.is_none(), **free))
+            .map(|(i, _)| i)
+            .expect("lanes have at least one slot");
+
+        let finish = lanes.slots[slot].and_then(|free| {
+            let start = free.max(lanes.next_start);
+            lanes.next_start = start + lanes.interval;
+            estimate.map(|estimate| start + estimate)
+        });
+        lanes.slots[slot] = finish;
+        finish
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::tests::entry;
+
+    fn secs(value: u64) -> Option<Duration> {
+        Some(Duration::from_secs(value))
+    }
+
+    fn jobs(specs: &[(&str, Option<Duration>)]) -> Vec<(String, Option<Duration>)> {
+        specs.iter().map(|(owner, estimate)| (owner.to_string(), *estimate)).collect()
+    }
+
+    #[test]
+    fn policies_parse_from_cli_names() {
+        assert_eq!(QueuePolicy::from_name("sjf"), Some(QueuePolicy::ShortestFirst));
+        assert_eq!(QueuePolicy::from_name("fair"), Some(QueuePolicy::FairShare));
+        assert_eq!(QueuePolicy::from_name("fifo"), Some(QueuePolicy::Fifo));
+        assert_eq!(QueuePolicy::from_name("lifo"), None);
+    }
+
+    #[test]
+    fn fifo_keeps_submission_order() {
+        let queue = jobs(&[("", secs(30)), ("", None), ("", secs(10))]);
+        assert_eq!(order(QueuePolicy::Fifo, &queue, &[]), [0, 1, 2]);
+    }
+
+    #[test]
+    fn shortest_first_puts_unknown_estimates_last() {
+        let queue = jobs(&[("", secs(30)), ("", None), ("", secs(10)), ("", secs(30)), ("", None)]);
+        // Ties keep submission order
+        assert_eq!(order(QueuePolicy::ShortestFirst, &queue, &[]), [2, 0, 3, 1, 4]);
+    }
+
+    #[test]
+    fn fair_share_alternates_owners_and_counts_past_usage() {
+        let queue = jobs(&[("a", secs(10)), ("a", secs(10)), ("b", secs(10)), ("b", secs(10))]);
+        assert_eq!(order(QueuePolicy::FairShare, &queue, &[]), [0, 2, 1, 3]);
+
+        // "a" already used 15 s, so "b" catches up first
+        let past = vec![HistoryEntry { owner: "a".to_string(), ..entry("blastn", "nt", 100, 15.0) }];
+        assert_eq!(order(QueuePolicy::FairShare, &queue, &past), [2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn fair_share_charges_unestimated_jobs_the_average() {
+        // Charged nothing, "a" would run both its jobs before "b" got a turn
+        let queue = jobs(&[("a", None), ("a", None), ("b", secs(1)), ("b", secs(1))]);
+        assert_eq!(order(QueuePolicy::FairShare, &queue, &[]), [0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn ungoverned_engines_finish_after_their_estimate() {
+        let mut planner = EtaPlanner::default();
+        assert_eq!(planner.schedule("rust", secs(10)), secs(10));
+        assert_eq!(planner.schedule("rust", secs(20)), secs(20));
+        assert_eq!(planner.schedule("rust", None), None);
+    }
+
+    #[test]
+    fn governed_engines_queue_for_slots_and_spacing() {
+        let limits = HashMap::from([
+            ("serial".to_string(), (1, Duration::ZERO)),
+            ("spaced".to_string(), (2, Duration::from_secs(5))),
+        ]);
+        let mut planner = EtaPlanner::new(limits);
+
+        assert_eq!(planner.schedule("serial", secs(10)), secs(10));
+        assert_eq!(planner.schedule("serial", secs(20)), secs(30));
+
+        // Second slot, but spaced one interval after the first start
+        assert_eq!(planner.schedule("spaced", secs(10)), secs(10));
+        assert_eq!(planner.schedule("spaced", secs(10)), secs(15));
+        // Waits for the first slot to free up at 10 s
+        assert_eq!(planner.schedule("spaced", secs(10)), secs(20));
+    }
+
+    #[test]
+    fn unknown_estimates_make_later_etas_unknown_in_that_slot() {
+        let mut planner = EtaPlanner::new(HashMap::from([
+            ("one".to_string(), (1, Duration::ZERO)),
+            ("two".to_string(), (2, Duration::ZERO)),
+        ]));
+
+        assert_eq!(planner.schedule("one", None), None);
+        assert_eq!(planner.schedule("one", secs(10)), None);
+
+        // The slot with a known free time is used first
+        assert_eq!(planner.schedule("two", None), None);
+        assert_eq!(planner.schedule("two", secs(10)), secs(10));
+        assert_eq!(planner.schedule("two", secs(10)), secs(20));
+    }
+}
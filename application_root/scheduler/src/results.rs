@@ -0,0 +1,455 @@
+// -----------------------------
+// BLAST RESULT TYPES AND XML PARSER
+// -----------------------------
+// Engines hand back BLAST XML (outfmt 5), DIAMOND or HMMER tabular output.
+// Everything downstream of an engine (UI export, reporting) works on these
+// types instead of the raw files.
+use std::path::Path;
+
+#[derive(Debug, Clone, Default)]
+pub struct BlastReport {
+    pub program: String,
+    pub database: String,
+    pub queries: Vec<QueryResult>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct QueryResult {
+    pub query_id: String,
+    pub query_def: String,
+    pub query_len: u64,
+    pub hits: Vec<Hit>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Hit {
+    pub id: String,
+    pub def: String,
+    pub accession: String,
+    pub len: u64,
+    pub hsps: Vec<Hsp>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Hsp {
+    pub bit_score: f64,
+    pub score: f64,
+    pub evalue: f64,
+    pub query_from: u64,
+    pub query_to: u64,
+    pub hit_from: u64,
+    pub hit_to: u64,
+    pub query_frame: i32,
+    pub hit_frame: i32,
+    pub identity: u64,
+    pub positive: u64,
+    pub gaps: u64,
+    pub align_len: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strand {
+    Plus,
+    Minus,
+}
+
+impl Strand {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Strand::Plus => "+",
+            Strand::Minus => "-",
+        }
+    }
+}
+
+impl Hsp {
+    /// Percent identity over the aligned length.
+    pub fn percent_identity(&self) -> f64 {
+        if self.align_len == 0 {
+            return 0.0;
+        }
+        self.identity as f64 * 100.0 / self.align_len as f64
+    }
+
+    /// Orientation of the subject relative to the query. A frame of 0 means
+    /// "not applicable" (protein side), so only non-zero frames count.
+    pub fn strand(&self) -> Strand {
+        let sign = self.query_frame.signum() * self.hit_frame.signum();
+        let minus = sign < 0 || (sign == 0 && (self.query_frame < 0 || self.hit_frame < 0));
+        if minus { Strand::Minus } else { Strand::Plus }
+    }
+
+    /// Subject coordinates as an ordered (start, end) pair, 1-based inclusive.
+    pub fn hit_range(&self) -> (u64, u64) {
+        (self.hit_from.min(self.hit_to), self.hit_from.max(self.hit_to))
+    }
+}
+
+#[derive(Debug)]
+pub enum ResultParseError {
+    Io(String),
+    Malformed(String),
+    InvalidValue(String),
+}
+
+/// Loads BLAST XML, HMMER --domtblout (which opens with a `#` header), or
+/// DIAMOND tabular output.
+pub async fn load_report(path: &Path) -> Result<BlastReport, ResultParseError> {
+    // Gzipped results are decompressed transparently
+    let bytes = crate::compression::read_result(path)
+        .await
+        .map_err(ResultParseError::Io)?;
+    let text = String::from_utf8(bytes)
+        .map_err(|_| ResultParseError::Malformed(format!("{:?} is not UTF-8 text", path)))?;
+    let start = text.trim_start();
+    if start.starts_with('<') {
+        parse_blast_xml(&text)
+    } else if start.starts_with('#') {
+        crate::hmmer::parse_domtblout(&text)
+    } else {
+        crate::diamond::parse_diamond_tabular(&text)
+    }
+}
+
+pub fn parse_blast_xml(xml: &str) -> Result<BlastReport, ResultParseError> {
+    let mut report = BlastReport::default();
+    let mut query: Option<QueryResult> = None;
+    let mut hit: Option<Hit> = None;
+    let mut hsp: Option<Hsp> = None;
+    let mut current: Option<&str> = None;
+
+    for event in XmlEvents::new(xml) {
+        match event? {
+            XmlEvent::Start(name) => {
+                match name {
+                    "Iteration" => query = Some(QueryResult::default()),
+                    "Hit" => hit = Some(Hit::default()),
+                    "Hsp" => hsp = Some(Hsp::default()),
+                    _ => {}
+                }
+                current = Some(name);
+            }
+            XmlEvent::End(name) => {
+                match name {
+                    "Hsp" => {
+                        if let (Some(done), Some(h)) = (hsp.take(), hit.as_mut()) {
+                            h.hsps.push(done);
+                        }
+                    }
+                    "Hit" => {
+                        if let (Some(done), Some(q)) = (hit.take(), query.as_mut()) {
+                            q.hits.push(done);
+                        }
+                    }
+                    "Iteration" => {
+                        if let Some(done) = query.take() {
+                            report.queries.push(done);
+                        }
+                    }
+                    _ => {}
+                }
+                current = None;
+            }
+            XmlEvent::Text(raw) => {
+                let Some(tag) = current else { continue };
+                let text = unescape(raw.trim());
+                if text.is_empty() {
+                    continue;
+                }
+
+                if let Some(h) = hsp.as_mut() {
+                    assign_hsp_field(h, tag, &text)?;
+                } else if let Some(h) = hit.as_mut() {
+                    match tag {
+                        "Hit_id" => h.id = text,
+                        "Hit_def" => h.def = text,
+                        "Hit_accession" => h.accession = text,
+                        "Hit_len" => h.len = parse_number(tag, &text)?,
+                        _ => {}
+                    }
+                } else if let Some(q) = query.as_mut() {
+                    match tag {
+                        "Iteration_query-ID" => q.query_id = text,
+                        "Iteration_query-def" => q.query_def = text,
+                        "Iteration_query-len" => q.query_len = parse_number(tag, &text)?,
+                        _ => {}
+                    }
+                } else {
+                    match tag {
+                        "BlastOutput_program" => report.program = text,
+                        "BlastOutput_db" => report.database = text,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    if query.is_some() || hit.is_some() || hsp.is_some() {
+        return Err(ResultParseError::Malformed("Unexpected end of BLAST XML".to_string()));
+    }
+
+    Ok(report)
+}
+
+fn assign_hsp_field(hsp: &mut Hsp, tag: &str, text: &str) -> Result<(), ResultParseError> {
+    match tag {
+        "Hsp_bit-score" => hsp.bit_score = parse_number(tag, text)?,
+        "Hsp_score" => hsp.score = parse_number(tag, text)?,
+        "Hsp_evalue" => hsp.evalue = parse_number(tag, text)?,
+        "Hsp_query-from" => hsp.query_from = parse_number(tag, text)?,
+        "Hsp_query-to" => hsp.query_to = parse_number(tag, text)?,
+        "Hsp_hit-from" => hsp.hit_from = parse_number(tag, text)?,
+        "Hsp_hit-to" => hsp.hit_to = parse_number(tag, text)?,
+        "Hsp_query-frame" => hsp.query_frame = parse_number(tag, text)?,
+        "Hsp_hit-frame" => hsp.hit_frame = parse_number(tag, text)?,
+        "Hsp_identity" => hsp.identity = parse_number(tag, text)?,
+        "Hsp_positive" => hsp.positive = parse_number(tag, text)?,
+        "Hsp_gaps" => hsp.gaps = parse_number(tag, text)?,
+        "Hsp_align-len" => hsp.align_len = parse_number(tag, text)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn parse_number<T: std::str::FromStr>(tag: &str, text: &str) -> Result<T, ResultParseError> {
+    text.parse()
+        .map_err(|_| ResultParseError::InvalidValue(format!("{}: '{}'", tag, text)))
+}
+
+fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// -----------------------------
+// Minimal XML tokenizer
+// -----------------------------
+// BLAST XML has no attributes we care about and no mixed content, so a
+// start/end/text scanner is all the parser needs.
+enum XmlEvent<'a> {
+    Start(&'a str),
+    End(&'a str),
+    Text(&'a str),
+}
+
+struct XmlEvents<'a> {
+    xml: &'a str,
+    pos: usize,
+}
+
+impl<'a> XmlEvents<'a> {
+    fn new(xml: &'a str) -> Self {
+        Self { xml, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for XmlEvents<'a> {
+    type Item = Result<XmlEvent<'a>, ResultParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let rest = &self.xml[self.pos..];
+            if rest.is_empty() {
+                return None;
+            }
+
+            if !rest.starts_with('<') {
+                let end = rest.find('<').unwrap_or(rest.len());
+                self.pos += end;
+                return Some(Ok(XmlEvent::Text(&rest[..end])));
+            }
+
+            let Some(close) = rest.find('>') else {
+                return Some(Err(ResultParseError::Malformed(
+                    format!("Unterminated tag at byte {}", self.pos)
+                )));
+            };
+            let tag = &rest[1..close];
+            self.pos += close + 1;
+
+            // Declarations, doctype and comments carry no data
+            if tag.starts_with('?') || tag.starts_with('!') {
+                continue;
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                return Some(Ok(XmlEvent::End(name.trim())));
+            }
+            // Self-closing elements are empty, so they never carry a value
+            if tag.ends_with('/') {
+                continue;
+            }
+            let name = tag.split_whitespace().next().unwrap_or("");
+            return Some(Ok(XmlEvent::Start(name)));
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Trimmed blastn -outfmt 5 output: two queries, the second hitting the
+    // minus strand, with escaped deflines
+    pub(crate) const TWO_QUERY_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE BlastOutput PUBLIC "-//NCBI//NCBI BlastOutput/EN" "http://www.ncbi.nlm.nih.gov/dtd/NCBI_BlastOutput.dtd">
+<BlastOutput>
+  <BlastOutput_program>blastn</BlastOutput_program>
+  <BlastOutput_version>BLASTN 2.15.0+</BlastOutput_version>
+  <BlastOutput_db>core_nt</BlastOutput_db>
+  <BlastOutput_param>
+    <Parameters>
+      <Parameters_expect>10</Parameters_expect>
+      <Parameters_filter>L;m;</Parameters_filter>
+    </Parameters>
+  </BlastOutput_param>
+  <BlastOutput_iterations>
+    <Iteration>
+      <Iteration_iter-num>1</Iteration_iter-num>
+      <Iteration_query-ID>Query_1</Iteration_query-ID>
+      <Iteration_query-def>seq1 &quot;forward&quot; &amp; friends</Iteration_query-def>
+      <Iteration_query-len>30</Iteration_query-len>
+      <Iteration_hits>
+        <Hit>
+          <Hit_num>1</Hit_num>
+          <Hit_id>gi|123|gb|AB000001.1|</Hit_id>
+          <Hit_def>Homo sapiens &lt;partial&gt; gene</Hit_def>
+          <Hit_accession>AB000001</Hit_accession>
+          <Hit_len>1500</Hit_len>
+          <Hit_hsps>
+            <Hsp>
+              <Hsp_num>1</Hsp_num>
+              <Hsp_bit-score>55.4</Hsp_bit-score>
+              <Hsp_score>30</Hsp_score>
+              <Hsp_evalue>4.2e-07</Hsp_evalue>
+              <Hsp_query-from>1</Hsp_query-from>
+              <Hsp_query-to>30</Hsp_query-to>
+              <Hsp_hit-from>101</Hsp_hit-from>
+              <Hsp_hit-to>130</Hsp_hit-to>
+              <Hsp_query-frame>1</Hsp_query-frame>
+              <Hsp_hit-frame>1</Hsp_hit-frame>
+              <Hsp_identity>29</Hsp_identity>
+              <Hsp_positive>29</Hsp_positive>
+              <Hsp_gaps>0</Hsp_gaps>
+              <Hsp_align-len>30</Hsp_align-len>
+              <Hsp_qseq>ACGTACGTACGTACGTACGTACGTACGTAC</Hsp_qseq>
+            </Hsp>
+          </Hit_hsps>
+        </Hit>
+      </Iteration_hits>
+      <Iteration_stat>
+        <Statistics>
+          <Statistics_db-num>1000</Statistics_db-num>
+        </Statistics>
+      </Iteration_stat>
+    </Iteration>
+    <Iteration>
+      <Iteration_iter-num>2</Iteration_iter-num>
+      <Iteration_query-ID>Query_2</Iteration_query-ID>
+      <Iteration_query-def>seq2</Iteration_query-def>
+      <Iteration_query-len>40</Iteration_query-len>
+      <Iteration_hits>
+        <Hit>
+          <Hit_num>1</Hit_num>
+          <Hit_id>gi|456|gb|CD000002.1|</Hit_id>
+          <Hit_def>Mus musculus clone</Hit_def>
+          <Hit_accession>CD000002</Hit_accession>
+          <Hit_len>900</Hit_len>
+          <Hit_hsps>
+            <Hsp>
+              <Hsp_num>1</Hsp_num>
+              <Hsp_bit-score>73.1</Hsp_bit-score>
+              <Hsp_score>39</Hsp_score>
+              <Hsp_evalue>2e-12</Hsp_evalue>
+              <Hsp_query-from>1</Hsp_query-from>
+              <Hsp_query-to>40</Hsp_query-to>
+              <Hsp_hit-from>540</Hsp_hit-from>
+              <Hsp_hit-to>501</Hsp_hit-to>
+              <Hsp_query-frame>1</Hsp_query-frame>
+              <Hsp_hit-frame>-1</Hsp_hit-frame>
+              <Hsp_identity>38</Hsp_identity>
+              <Hsp_positive>38</Hsp_positive>
+              <Hsp_gaps>1</Hsp_gaps>
+              <Hsp_align-len>40</Hsp_align-len>
+            </Hsp>
+          </Hit_hsps>
+        </Hit>
+      </Iteration_hits>
+    </Iteration>
+    <Iteration>
+      <Iteration_iter-num>3</Iteration_iter-num>
+      <Iteration_query-ID>Query_3</Iteration_query-ID>
+      <Iteration_query-def>seq3</Iteration_query-def>
+      <Iteration_query-len>25</Iteration_query-len>
+      <Iteration_hits/>
+      <Iteration_message>No hits found</Iteration_message>
+    </Iteration>
+  </BlastOutput_iterations>
+</BlastOutput>
+"#;
+
+    #[test]
+    fn parses_every_query_in_order() {
+        let report = parse_blast_xml(TWO_QUERY_XML).unwrap();
+        assert_eq!(report.program, "blastn");
+        assert_eq!(report.database, "core_nt");
+
+        let ids: Vec<&str> = report.queries.iter().map(|q| q.query_id.as_str()).collect();
+        assert_eq!(ids, ["Query_1", "Query_2", "Query_3"]);
+        assert_eq!(report.queries[1].query_len, 40);
+        assert!(report.queries[2].hits.is_empty());
+    }
+
+    #[test]
+    fn reads_hsp_fields() {
+        let report = parse_blast_xml(TWO_QUERY_XML).unwrap();
+        let hit = &report.queries[0].hits[0];
+        assert_eq!(hit.accession, "AB000001");
+        assert_eq!(hit.len, 1500);
+
+        let hsp = &hit.hsps[0];
+        assert_eq!(hsp.bit_score, 55.4);
+        assert_eq!(hsp.evalue, 4.2e-7);
+        assert_eq!((hsp.query_from, hsp.query_to), (1, 30));
+        assert_eq!((hsp.hit_from, hsp.hit_to), (101, 130));
+        assert_eq!(hsp.identity, 29);
+        assert_eq!(hsp.align_len, 30);
+        assert_eq!(hsp.strand(), Strand::Plus);
+    }
+
+    #[test]
+    fn minus_strand_hits_have_ordered_ranges() {
+        let report = parse_blast_xml(TWO_QUERY_XML).unwrap();
+        let hsp = &report.queries[1].hits[0].hsps[0];
+        assert_eq!(hsp.hit_frame, -1);
+        assert_eq!(hsp.strand(), Strand::Minus);
+        assert_eq!(hsp.hit_range(), (501, 540));
+        assert_eq!(hsp.percent_identity(), 95.0);
+    }
+
+    #[test]
+    fn unescapes_entities() {
+        let report = parse_blast_xml(TWO_QUERY_XML).unwrap();
+        assert_eq!(report.queries[0].query_def, r#"seq1 "forward" & friends"#);
+        assert_eq!(report.queries[0].hits[0].def, "Homo sapiens <partial> gene");
+        assert_eq!(unescape("&amp;lt;"), "&lt;");
+    }
+
+    #[test]
+    fn rejects_truncated_output() {
+        let truncated = &TWO_QUERY_XML[..TWO_QUERY_XML.find("<Iteration_stat>").unwrap()];
+        assert!(matches!(parse_blast_xml(truncated), Err(ResultParseError::Malformed(_))));
+    }
+
+    #[test]
+    fn rejects_non_numeric_values() {
+        let bad = TWO_QUERY_XML.replace("<Hit_len>900</Hit_len>", "<Hit_len>n/a</Hit_len>");
+        assert!(matches!(parse_blast_xml(&bad), Err(ResultParseError::InvalidValue(_))));
+    }
+}
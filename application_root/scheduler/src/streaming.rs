@@ -0,0 +1,192 @@
+// -----------------------------
+// CHILD OUTPUT STREAMING
+// -----------------------------
+// Copies an engine process's stdout to its result file as it is produced,
+// forwards stderr to the log line by line, and reports progress along the
+// way, so result size is bounded by disk rather than memory.
+//
+// Progress goes to stdout as machine-readable lines, which the UI picks out
+// of the scheduler's output:
+//   PROGRESS {"job_id":3,"bytes":65536,"records":812,"finished":false}
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::fs;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, BufWriter};
+use tokio::process::Command;
+use tokio::time::Instant;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Minimum time between two progress events for the same stream.
+pub const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Starts every progress line; the rest of the line is the event as JSON.
+pub const PROGRESS_PREFIX: &str = "PROGRESS ";
+
+/// Output written so far. Records are newline-terminated lines, which for
+/// tabular and text engine output is one record per line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ProgressEvent {
+    pub job_id: u64,
+    pub bytes: u64,
+    pub records: u64,
+    pub finished: bool,
+}
+
+impl fmt::Display for ProgressEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = if self.finished { "done" } else { "running" };
+        write!(
+            f,
+            "📈 Job {} output {}: {} bytes, {} records",
+            self.job_id, state, self.bytes, self.records
+        )
+    }
+}
+
+impl ProgressEvent {
+    /// `PROGRESS_PREFIX` followed by the event as JSON.
+    pub fn to_line(&self) -> String {
+        let json = serde_json::to_string(self).expect("progress events always serialize");
+        format!("{}{}", PROGRESS_PREFIX, json)
+    }
+}
+
+struct ProgressTracker {
+    event: ProgressEvent,
+    last_report: Instant,
+}
+
+impl ProgressTracker {
+    fn new(job_id: u64) -> Self {
+        Self {
+            event: ProgressEvent { job_id, ..ProgressEvent::default() },
+            last_report: Instant::now(),
+        }
+    }
+
+    fn record(&mut self, chunk: &[u8]) {
+        self.event.bytes += chunk.len() as u64;
+        self.event.records += chunk.iter().filter(|&&b| b == b'\n').count() as u64;
+        if self.last_report.elapsed() >= PROGRESS_INTERVAL {
+            crate::log!("{}", self.event.to_line());
+            self.last_report = Instant::now();
+        }
+    }
+
+    fn finish(mut self, trailing_partial_line: bool) -> ProgressEvent {
+        if trailing_partial_line {
+            self.event.records += 1;
+        }
+        self.event.finished = true;
+        crate::log!("{}", self.event.to_line());
+        self.event
+    }
+}
+
+/// Copies `reader` into a new file at `path`, emitting progress events for
+/// `job_id` at most every `PROGRESS_INTERVAL`, and a final one at EOF.
+/// With `compress`, the file is gzipped as it is written; progress still
+/// counts the uncompressed bytes.
+pub async fn stream_to_file<R>(
+    reader: R,
+    path: &Path,
+    job_id: u64,
+    compress: bool,
+) -> std::io::Result<ProgressEvent>
+where
+    R: AsyncRead + Unpin,
+{
+    if !compress {
+        let file = BufWriter::new(fs::File::create(path).await?);
+        return copy_with_progress(reader, file, job_id).await;
+    }
+
+    let file = std::fs::File::create(path)?;
+    let mut gzip = Command::new("gzip")
+        .arg("-c")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::from(file))
+        .spawn()?;
+    let stdin = gzip.stdin.take().expect("stdin is piped");
+
+    let copied = copy_with_progress(reader, stdin, job_id).await;
+    let status = gzip.wait().await?;
+    let event = copied?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("gzip exited with {}", status)));
+    }
+    Ok(event)
+}
+
+// The writer is dropped on return, which closes a pipe to a child process
+async fn copy_with_progress<R, W>(mut reader: R, mut writer: W, job_id: u64) -> std::io::Result<ProgressEvent>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut tracker = ProgressTracker::new(job_id);
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    let mut last_byte = b'\n';
+
+    loop {
+        let read = reader.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&chunk[..read]).await?;
+        tracker.record(&chunk[..read]);
+        last_byte = chunk[read - 1];
+    }
+
+    writer.flush().await?;
+    Ok(tracker.finish(last_byte != b'\n'))
+}
+
+/// Forwards each line of `reader` to the log, prefixed with `label`.
+/// Invalid UTF-8 is logged lossily rather than ending the stream, so a
+/// child's stderr is always drained to EOF.
+pub async fn log_lines<R>(reader: R, label: String) -> std::io::Result<()>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line).await? == 0 {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(&line);
+        crate::log!("[{}] {}", label, text.trim_end_matches(['\r', '\n']));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn counts_records_and_reports_a_final_progress_line() {
+        let path = std::env::temp_dir().join(format!("nucloflo_stream_{}.tsv", std::process::id()));
+        let event = stream_to_file(&b"a\tb\nc\td\ne"[..], &path, 7, false).await.unwrap();
+        let written = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(written, b"a\tb\nc\td\ne");
+        assert_eq!(event, ProgressEvent { job_id: 7, bytes: 9, records: 3, finished: true });
+        assert_eq!(event.to_line(), r#"PROGRESS {"job_id":7,"bytes":9,"records":3,"finished":true}"#);
+    }
+
+    #[tokio::test]
+    async fn log_lines_reads_past_invalid_utf8() {
+        // Drained means nothing is left after the invalid line
+        let stderr: &[u8] = b"ok\n\xff\xfe bad\nlast";
+        let mut reader = stderr;
+        log_lines(&mut reader, "test".to_string()).await.unwrap();
+        assert!(reader.is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+// -----------------------------
+// TEST HARNESS
+// -----------------------------
+// A scripted fake engine and a scheduler that runs in a throwaway directory,
+// so pipelines embedding NucloFlo can be tested without BLAST+ or network.
+//
+//   let engine = MockEngine::new()
+//       .with_delay(Duration::from_millis(50))
+//       .then_fail(BlastEngineError::Throttled("busy".to_string()));
+//   let harness = TestScheduler::new(engine);
+//   // Without this the harness is unthrottled and the failure is final;
+//   // with a rate limit the throttled call is retried
+//   std::fs::write(harness.root().join("engines.json"), r#"{ "python": { "rate_limit":
+//       { "requests_per_sec": 100, "max_concurrent": 1, "cooldown_secs": 0 } } }"#)?;
+//   let input = harness.write_input("query.fasta", ">q1\nACGT\n")?;
+//   let outcomes = harness.run(vec![harness.job(1, input)]).await;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs;
+
+use crate::planning::QueuePolicy;
+use crate::{
+    BlastEngine, BlastEngineError, BlastExecutionRequest, BlastResult, Job, JobOutcome,
+    ResultOutput, ResultStatus, Scheduler,
+};
+
+/// One hit for one query; parses with `results::parse_blast_xml`.
+pub const CANNED_BLAST_XML: &str = r#"<?xml version="1.0"?>
+<BlastOutput>
+  <BlastOutput_program>blastn</BlastOutput_program>
+  <BlastOutput_db>mock</BlastOutput_db>
+  <BlastOutput_iterations>
+    <Iteration>
+      <Iteration_query-ID>Query_1</Iteration_query-ID>
+      <Iteration_query-def>mock query</Iteration_query-def>
+      <Iteration_query-len>100</Iteration_query-len>
+      <Iteration_hits>
+        <Hit>
+          <Hit_id>mock|1</Hit_id>
+          <Hit_def>Mock subject</Hit_def>
+          <Hit_accession>MOCK0001</Hit_accession>
+          <Hit_len>100</Hit_len>
+          <Hit_hsps>
+            <Hsp>
+              <Hsp_bit-score>185.0</Hsp_bit-score>
+              <Hsp_score>100</Hsp_score>
+              <Hsp_evalue>1e-50</Hsp_evalue>
+              <Hsp_query-from>1</Hsp_query-from>
+              <Hsp_query-to>100</Hsp_query-to>
+              <Hsp_hit-from>1</Hsp_hit-from>
+              <Hsp_hit-to>100</Hsp_hit-to>
+              <Hsp_query-frame>1</Hsp_query-frame>
+              <Hsp_hit-frame>1</Hsp_hit-frame>
+              <Hsp_identity>100</Hsp_identity>
+              <Hsp_positive>100</Hsp_positive>
+              <Hsp_gaps>0</Hsp_gaps>
+              <Hsp_align-len>100</Hsp_align-len>
+            </Hsp>
+          </Hit_hsps>
+        </Hit>
+      </Iteration_hits>
+    </Iteration>
+  </BlastOutput_iterations>
+</BlastOutput>
+"#;
+
+#[derive(Debug, Clone)]
+enum MockResponse {
+    Output(String),
+    Fail(BlastEngineError),
+}
+
+#[derive(Debug)]
+struct MockScript {
+    // One-shot responses, used in call order before the default
+    queued: VecDeque<MockResponse>,
+    calls: Vec<u64>,
+}
+
+/// Engine that answers from a script instead of running a search. Each
+/// call sleeps for the configured delay, then takes the next scripted
+/// response, falling back to the default output once the script runs out.
+/// Outputs are written to `mock_<job_id>.xml` in the request's output dir.
+#[derive(Debug)]
+pub struct MockEngine {
+    delay: Duration,
+    default_output: String,
+    script: Mutex<MockScript>,
+}
+
+impl Default for MockEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockEngine {
+    pub fn new() -> Self {
+        Self {
+            delay: Duration::ZERO,
+            default_output: CANNED_BLAST_XML.to_string(),
+            script: Mutex::new(MockScript { queued: VecDeque::new(), calls: Vec::new() }),
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Output for every call not covered by the script.
+    pub fn with_output(mut self, output: impl Into<String>) -> Self {
+        self.default_output = output.into();
+        self
+    }
+
+    /// Answers the next unscripted call with `output`.
+    pub fn then_output(self, output: impl Into<String>) -> Self {
+        self.push(MockResponse::Output(output.into()))
+    }
+
+    /// Fails the next unscripted call with `error`.
+    pub fn then_fail(self, error: BlastEngineError) -> Self {
+        self.push(MockResponse::Fail(error))
+    }
+
+    fn push(self, response: MockResponse) -> Self {
+        self.script.lock().expect("mock script lock poisoned").queued.push_back(response);
+        self
+    }
+
+    /// Job ids of every call so far, in call order (retries included).
+    pub fn calls(&self) -> Vec<u64> {
+        self.script.lock().expect("mock script lock poisoned").calls.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl BlastEngine for MockEngine {
+    fn name(&self) -> &'static str { "Mock engine" }
+
+    async fn execute(&self, request: BlastExecutionRequest) -> Result<BlastResult, BlastEngineError> {
+        let response = {
+            let mut script = self.script.lock().expect("mock script lock poisoned");
+            script.calls.push(request.job_id);
+            script.queued.pop_front()
+        };
+
+        tokio::time::sleep(self.delay).await;
+
+        let output = match response {
+            Some(MockResponse::Fail(err)) => return Err(err),
+            Some(MockResponse::Output(output)) => output,
+            None => self.default_output.clone(),
+        };
+
+        fs::create_dir_all(&request.output_dir).await
+            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Cannot create output dir: {}", e)))?;
+        let output_path = request.output_dir.join(format!("mock_{}.xml", request.job_id));
+        fs::write(&output_path, output).await
+            .map_err(|e| BlastEngineError::ExecutionFailed(format!("Cannot write output: {}", e)))?;
+
+        Ok(BlastResult {
+            job_id: request.job_id,
+            status: ResultStatus::Success,
+            output: ResultOutput::FilePath(output_path),
+        })
+    }
+}
+
+// Overrides every built-in rate limit (only "python" has one)
+const UNTHROTTLED_ENGINES: &str = r#"{ "rust": {}, "python": {}, "diamond": {}, "hmmer": {} }"#;
+
+// Distinguishes harnesses created by one process in the same instant
+static HARNESS_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Scheduler wired to a `MockEngine` and rooted in a fresh temp directory,
+/// which is removed when the harness is dropped. Registry files
+/// (engines.json, databases.json) may be written into `root()` before `run`.
+/// Jobs are routed to engine ids as usual and the mock runs under that id's
+/// settings (remote BLAST jobs go to "python"). Without an engines.json no
+/// engine is governed, so runs never wait and throttled calls aren't retried.
+pub struct TestScheduler {
+    root: PathBuf,
+    engine: Arc<MockEngine>,
+    policy: QueuePolicy,
+}
+
+impl TestScheduler {
+    pub fn new(engine: MockEngine) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let root = std::env::temp_dir().join(format!(
+            "nucloflo_test_{}_{}_{}",
+            std::process::id(),
+            HARNESS_COUNTER.fetch_add(1, Ordering::Relaxed),
+            nanos
+        ));
+        std::fs::create_dir_all(&root).expect("cannot create test scheduler directory");
+        Self { root, engine: Arc::new(engine), policy: QueuePolicy::default() }
+    }
+
+    pub fn with_policy(mut self, policy: QueuePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn output_dir(&self) -> PathBuf {
+        self.root.join("outputs")
+    }
+
+    pub fn engine(&self) -> &MockEngine {
+        &self.engine
+    }
+
+    /// Writes `contents` to `name` under the harness root and returns the path.
+    pub fn write_input(&self, name: &str, contents: &str) -> std::io::Result<PathBuf> {
+        let path = self.root.join(name);
+        std::fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    pub fn job(&self, id: u32, input_path: PathBuf) -> Job {
+        Job::new(id, input_path)
+    }
+
+    pub async fn run(&self, jobs: Vec<Job>) -> Vec<JobOutcome> {
+        let engines_json = self.root.join("engines.json");
+        if !engines_json.exists() {
+            std::fs::write(&engines_json, UNTHROTTLED_ENGINES).expect("cannot write test engine registry");
+        }
+        Scheduler::with_root(jobs, self.root.clone())
+            .with_engine(Arc::clone(&self.engine) as Arc<dyn BlastEngine + Send + Sync>)
+            .with_policy(self.policy)
+            .run()
+            .await
+    }
+}
+
+impl Drop for TestScheduler {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
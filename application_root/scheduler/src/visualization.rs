@@ -0,0 +1,133 @@
+// -----------------------------
+// VISUALIZATION EXPORT
+// -----------------------------
+// Compact JSON for the Electron hit map. Only what the UI draws is kept,
+// so the renderer never has to touch BLAST XML.
+use serde::Serialize;
+
+use crate::results::BlastReport;
+
+#[derive(Debug, Serialize)]
+pub struct VisualizationReport {
+    pub program: String,
+    pub database: String,
+    pub queries: Vec<QueryView>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryView {
+    pub query_id: String,
+    pub query_def: String,
+    pub query_len: u64,
+    pub hits: Vec<HitView>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HitView {
+    pub subject_id: String,
+    pub subject_def: String,
+    pub subject_len: u64,
+    pub hsps: Vec<HspView>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HspView {
+    pub query_start: u64,
+    pub query_end: u64,
+    pub subject_start: u64,
+    pub subject_end: u64,
+    pub strand: &'static str,
+    pub query_frame: i32,
+    pub subject_frame: i32,
+    pub identity: f64,
+    pub bitscore: f64,
+    pub evalue: f64,
+}
+
+impl VisualizationReport {
+    pub fn from_report(report: &BlastReport) -> Self {
+        let queries = report.queries.iter().map(|query| QueryView {
+            query_id: query.query_id.clone(),
+            query_def: query.query_def.clone(),
+            query_len: query.query_len,
+            hits: query.hits.iter().map(|hit| HitView {
+                subject_id: if hit.accession.is_empty() { hit.id.clone() } else { hit.accession.clone() },
+                subject_def: hit.def.clone(),
+                subject_len: hit.len,
+                hsps: hit.hsps.iter().map(|hsp| {
+                    let (subject_start, subject_end) = hsp.hit_range();
+                    HspView {
+                        query_start: hsp.query_from.min(hsp.query_to),
+                        query_end: hsp.query_from.max(hsp.query_to),
+                        subject_start,
+                        subject_end,
+                        strand: hsp.strand().symbol(),
+                        query_frame: hsp.query_frame,
+                        subject_frame: hsp.hit_frame,
+                        // Rounded so the payload stays small
+                        identity: (hsp.percent_identity() * 100.0).round() / 100.0,
+                        bitscore: hsp.bit_score,
+                        evalue: hsp.evalue,
+                    }
+                }).collect(),
+            }).collect(),
+        }).collect();
+
+        Self {
+            program: report.program.clone(),
+            database: report.database.clone(),
+            queries,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        // Serializing plain structs of strings and numbers cannot fail
+        serde_json::to_string(self).expect("visualization report is serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{parse_blast_xml, tests::TWO_QUERY_XML};
+
+    #[test]
+    fn subject_id_falls_back_to_the_hit_id() {
+        let mut report = parse_blast_xml(TWO_QUERY_XML).unwrap();
+        report.queries[0].hits[0].accession.clear();
+
+        let view = VisualizationReport::from_report(&report);
+        assert_eq!(view.queries[0].hits[0].subject_id, "gi|123|gb|AB000001.1|");
+        assert_eq!(view.queries[1].hits[0].subject_id, "CD000002");
+    }
+
+    #[test]
+    fn hsps_have_ordered_ranges_strand_and_rounded_identity() {
+        let view = VisualizationReport::from_report(&parse_blast_xml(TWO_QUERY_XML).unwrap());
+        assert_eq!((view.program.as_str(), view.database.as_str()), ("blastn", "core_nt"));
+        assert_eq!(view.queries.len(), 3);
+
+        let plus = &view.queries[0].hits[0].hsps[0];
+        assert_eq!((plus.query_start, plus.query_end), (1, 30));
+        assert_eq!((plus.subject_start, plus.subject_end), (101, 130));
+        assert_eq!(plus.strand, "+");
+        // 29 of 30
+        assert_eq!(plus.identity, 96.67);
+
+        let minus = &view.queries[1].hits[0].hsps[0];
+        assert_eq!((minus.subject_start, minus.subject_end), (501, 540));
+        assert_eq!(minus.strand, "-");
+        assert_eq!(minus.subject_frame, -1);
+        assert_eq!(minus.identity, 95.0);
+    }
+
+    #[test]
+    fn json_uses_the_ui_field_names() {
+        let json = VisualizationReport::from_report(&parse_blast_xml(TWO_QUERY_XML).unwrap()).to_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let hsp = &value["queries"][1]["hits"][0]["hsps"][0];
+        assert_eq!(hsp["strand"], "-");
+        assert_eq!(hsp["subject_start"], 501);
+        assert_eq!(value["queries"][0]["query_def"], "seq1 \"forward\" & friends");
+    }
+}
@@ -0,0 +1,138 @@
+// Runs the scheduler end to end on the mock engine: success, a scripted
+// failure, and throttled calls retried through the engine's governor.
+use scheduler::testing::{MockEngine, TestScheduler};
+use scheduler::{results, BlastEngineError, ResultOutput};
+
+// Blastn against "nt" routes to the python engine; keep its governor fast
+const ENGINES_JSON: &str = r#"{ "python": { "rate_limit": {
+    "requests_per_sec": 1000, "max_concurrent": 2, "cooldown_secs": 0, "max_retries": 2 } } }"#;
+
+fn harness(engine: MockEngine) -> TestScheduler {
+    let harness = TestScheduler::new(engine);
+    std::fs::write(harness.root().join("engines.json"), ENGINES_JSON).unwrap();
+    harness
+}
+
+#[tokio::test]
+async fn successful_job_writes_a_parsable_result_and_sidecar() {
+    let harness = harness(MockEngine::new());
+    let input = harness.write_input("query.fasta", ">q1\nACGT\n").unwrap();
+
+    let outcomes = harness.run(vec![harness.job(1, input)]).await;
+
+    assert_eq!(outcomes.len(), 1);
+    let result = outcomes[0].result.as_ref().expect("job succeeds");
+    let ResultOutput::FilePath(path) = &result.output;
+    let report = results::load_report(path).await.unwrap();
+    assert_eq!(report.queries.len(), 1);
+    assert!(scheduler::metadata::sidecar_path(path).exists());
+    assert_eq!(harness.engine().calls(), [1]);
+}
+
+#[tokio::test]
+async fn scripted_failure_is_reported_without_retry() {
+    let engine = MockEngine::new().then_fail(BlastEngineError::ExecutionFailed("boom".to_string()));
+    let harness = harness(engine);
+    let first = harness.write_input("a.fasta", ">a\nACGT\n").unwrap();
+    let second = harness.write_input("b.fasta", ">b\nACGT\n").unwrap();
+
+    let mut outcomes = harness.run(vec![harness.job(1, first), harness.job(2, second)]).await;
+    outcomes.sort_by_key(|outcome| outcome.job_id);
+
+    assert_eq!(outcomes.len(), 2);
+    let failed = outcomes.iter().filter(|o| matches!(o.result, Err(BlastEngineError::ExecutionFailed(_)))).count();
+    assert_eq!(failed, 1);
+    assert_eq!(outcomes.iter().filter(|o| o.result.is_ok()).count(), 1);
+    assert_eq!(harness.engine().calls().len(), 2);
+}
+
+#[tokio::test]
+async fn throttled_job_is_retried() {
+    let engine = MockEngine::new().then_fail(BlastEngineError::Throttled("busy".to_string()));
+    let harness = harness(engine);
+    let input = harness.write_input("query.fasta", ">q1\nACGT\n").unwrap();
+
+    let outcomes = harness.run(vec![harness.job(1, input)]).await;
+
+    assert!(outcomes[0].result.is_ok());
+    assert_eq!(harness.engine().calls(), [1, 1]);
+}
+
+#[tokio::test]
+async fn throttled_job_fails_once_retries_run_out() {
+    let throttled = || BlastEngineError::Throttled("busy".to_string());
+    let engine = MockEngine::new().then_fail(throttled()).then_fail(throttled()).then_fail(throttled());
+    let harness = harness(engine);
+    let input = harness.write_input("query.fasta", ">q1\nACGT\n").unwrap();
+
+    let outcomes = harness.run(vec![harness.job(1, input)]).await;
+
+    assert!(matches!(outcomes[0].result, Err(BlastEngineError::Throttled(_))));
+    assert_eq!(harness.engine().calls(), [1, 1, 1]);
+}
+
+#[tokio::test]
+async fn default_harness_is_unthrottled() {
+    let engine = MockEngine::new().then_fail(BlastEngineError::Throttled("busy".to_string()));
+    let harness = TestScheduler::new(engine);
+    let jobs = (1..=3)
+        .map(|id| harness.job(id, harness.write_input(&format!("q{}.fasta", id), ">q\nACGT\n").unwrap()))
+        .collect();
+
+    let started = std::time::Instant::now();
+    let outcomes = harness.run(jobs).await;
+
+    // python's built-in limit would space these 10 s apart
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(outcomes.len(), 3);
+    // No governor, so the throttled call is not retried
+    assert_eq!(outcomes.iter().filter(|o| o.result.is_err()).count(), 1);
+    assert_eq!(harness.engine().calls().len(), 3);
+}
+
+// Multi-threaded, like the CLI, so dispatched tasks really do race
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn governed_jobs_reach_the_engine_in_policy_order() {
+    use scheduler::history::{history_path, HistoryEntry};
+    use scheduler::planning::QueuePolicy;
+
+    // One slot, so the engine sees jobs exactly in the order they are served
+    let harness = TestScheduler::new(MockEngine::new()).with_policy(QueuePolicy::ShortestFirst);
+    std::fs::write(harness.root().join("engines.json"), r#"{ "python": { "rate_limit": {
+        "requests_per_sec": 1000, "max_concurrent": 1, "cooldown_secs": 0 } } }"#).unwrap();
+
+    // Past runs: runtime grows with query length
+    let history: String = [(100, 10.0), (1000, 100.0)]
+        .iter()
+        .map(|&(residues, secs)| {
+            let entry = HistoryEntry {
+                job_id: 0,
+                program: "blastn".to_string(),
+                database: "nt".to_string(),
+                engine: "Mock engine".to_string(),
+                owner: String::new(),
+                query_sequences: 1,
+                query_residues: residues,
+                duration_secs: secs,
+                succeeded: true,
+                finished_at: 0,
+            };
+            serde_json::to_string(&entry).unwrap() + "\n"
+        })
+        .collect();
+    std::fs::write(history_path(harness.root()), history).unwrap();
+
+    let sizes = [(1, 800), (2, 50), (3, 400), (4, 10)];
+    let jobs = sizes
+        .iter()
+        .map(|&(id, residues)| {
+            let fasta = format!(">q{}\n{}\n", id, "A".repeat(residues));
+            harness.job(id, harness.write_input(&format!("q{}.fasta", id), &fasta).unwrap())
+        })
+        .collect();
+
+    let outcomes = harness.run(jobs).await;
+
+    assert!(outcomes.iter().all(|o| o.result.is_ok()));
+    assert_eq!(harness.engine().calls(), [4, 2, 3, 1]);
+}
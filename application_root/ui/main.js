@@ -44,6 +44,24 @@ app.on('activate', () => {
 });
 
 
+// --- Scheduler Binary Location ---
+function schedulerBinaryPath() {
+    // 1. Define the binary name
+    const schedulerBinaryName = 'scheduler' + (process.platform === 'win32' ? '.exe' : ''); 
+
+    // 2. Define the CORRECT path to the compiled executable.
+    // ASSUMPTION: 'application_root' is the workspace root.
+    // Path: 'ui' -> 'application_root' -> 'target/release' -> 'scheduler'
+    return path.join(
+        __dirname, 
+        '..',         // Up to application_root/
+        'target', 
+        'release', 
+        schedulerBinaryName 
+    );
+}
+
+
 // ==========================================================
 // --- IPC LISTENER: 1. Handle File Selection Dialog ---
 // ==========================================================
@@ -81,34 +99,41 @@ ipcMain.on('select-file', (event) => {
 // --- IPC LISTENER: 2. Handle Rust Scheduler Execution ---
 // ==========================================================
 ipcMain.on('run-blast', (event, inputFilePath) => {
-    
-    // 1. Define the binary name
-    const schedulerBinaryName = 'scheduler' + (process.platform === 'win32' ? '.exe' : ''); 
+    console.log(`[Electron] Passing input file: ${inputFilePath}`);
+    runScheduler(event, [inputFilePath]);
+});
 
-    // 2. Define the CORRECT path to the compiled executable.
-    // ASSUMPTION: 'application_root' is the workspace root.
-    // Path: 'ui' -> 'application_root' -> 'target/release' -> 'scheduler'
-    const rustBinaryPath = path.join(
-        __dirname, 
-        '..',         // Up to application_root/
-        'target', 
-        'release', 
-        schedulerBinaryName 
-    );
+// Accessions are fetched from NCBI by the scheduler instead of a local FASTA
+ipcMain.on('run-accessions', (event, accessions) => {
+    console.log(`[Electron] Passing accessions: ${accessions.join(',')}`);
+    runScheduler(event, ['accessions', accessions.join(',')]);
+});
+
+function runScheduler(event, schedulerArgs) {
+    
+    // 1-2. Resolve the compiled scheduler executable
+    const rustBinaryPath = schedulerBinaryPath();
 
     console.log(`[Electron] Attempting to launch scheduler: ${rustBinaryPath}`);
-    console.log(`[Electron] Passing input file: ${inputFilePath}`);
 
     try {
         // 3. Spawn the Rust process
-        const rustProcess = spawn(rustBinaryPath, [inputFilePath]);
+        const rustProcess = spawn(rustBinaryPath, schedulerArgs);
 
         // --- Handle Output and Errors ---
 
+        // Progress lines become 'blast-job-progress' events; everything else is
+        // forwarded as output. Chunks can end mid-line, so keep the remainder.
+        let pending = '';
         rustProcess.stdout.on('data', (data) => {
-            const output = data.toString();
-            console.log(`[Rust STDOUT]: ${output}`);
-            event.sender.send('blast-job-output', output);
+            const lines = (pending + data.toString()).split('\n');
+            pending = lines.pop();
+            forwardOutput(event, lines);
+        });
+        rustProcess.stdout.on('end', () => {
+            if (pending) {
+                forwardOutput(event, [pending]);
+            }
         });
 
         rustProcess.stderr.on('data', (data) => {
@@ -139,4 +164,137 @@ ipcMain.on('run-blast', (event, inputFilePath) => {
         console.error(`[CRITICAL SPAWN ERROR]: ${error}`);
         event.sender.send('blast-job-error', `Critical Electron error during spawn: ${error.message}`);
     }
-});
\ No newline at end of file
+}
+
+
+// Matches streaming::PROGRESS_PREFIX in the scheduler
+const PROGRESS_PREFIX = 'PROGRESS ';
+
+function forwardOutput(event, lines) {
+    const output = [];
+    for (const line of lines) {
+        if (line.startsWith(PROGRESS_PREFIX)) {
+            try {
+                // { job_id, bytes, records, finished }
+                event.sender.send('blast-job-progress', JSON.parse(line.slice(PROGRESS_PREFIX.length)));
+                continue;
+            } catch (err) {
+                console.error(`[Electron] Unparsable progress line: ${line}`);
+            }
+        }
+        output.push(line);
+    }
+    if (output.length > 0) {
+        const text = output.join('\n') + '\n';
+        console.log(`[Rust STDOUT]: ${text}`);
+        event.sender.send('blast-job-output', text);
+    }
+}
+
+// ==========================================================
+// --- IPC HANDLER: 3. Visualization JSON for a Result File ---
+// ==========================================================
+// Resolves with the parsed hit map data so the renderer never handles BLAST XML.
+ipcMain.handle('get-visualization', (event, resultPath) => {
+    return new Promise((resolve, reject) => {
+        const rustProcess = spawn(schedulerBinaryPath(), ['results', resultPath]);
+        let stdout = '';
+        let stderr = '';
+
+        rustProcess.stdout.on('data', (data) => { stdout += data.toString(); });
+        rustProcess.stderr.on('data', (data) => { stderr += data.toString(); });
+
+        rustProcess.on('error', (err) => {
+            reject(new Error(`Failed to execute scheduler: ${err.message}`));
+        });
+
+        rustProcess.on('close', (code) => {
+            if (code !== 0) {
+                reject(new Error(stderr || `Scheduler exited with code ${code}`));
+                return;
+            }
+            try {
+                resolve(JSON.parse(stdout));
+            } catch (err) {
+                reject(new Error(`Invalid visualization output: ${err.message}`));
+            }
+        });
+    });
+});
+
+
+// ==========================================================
+// --- IPC HANDLER: 4. Save a Result File (gzipped or not) ---
+// ==========================================================
+// The scheduler decompresses stored .gz results unless the user saves as .gz.
+ipcMain.handle('download-result', async (event, resultPath) => {
+    const window = BrowserWindow.getFocusedWindow();
+    const suggestedName = path.basename(resultPath).replace(/\.gz$/, '');
+
+    const result = await dialog.showSaveDialog(window, {
+        title: 'Save BLAST Result',
+        defaultPath: suggestedName,
+    });
+    if (result.canceled || !result.filePath) {
+        return null;
+    }
+
+    return new Promise((resolve, reject) => {
+        const rustProcess = spawn(schedulerBinaryPath(), ['download', resultPath, result.filePath]);
+        let stderr = '';
+
+        rustProcess.stderr.on('data', (data) => { stderr += data.toString(); });
+
+        rustProcess.on('error', (err) => {
+            reject(new Error(`Failed to execute scheduler: ${err.message}`));
+        });
+
+        rustProcess.on('close', (code) => {
+            if (code !== 0) {
+                reject(new Error(stderr || `Scheduler exited with code ${code}`));
+                return;
+            }
+            resolve(result.filePath);
+        });
+    });
+});
+
+
+// ==========================================================
+// --- IPC HANDLER: 5. Planned Queue with Runtime Estimates ---
+// ==========================================================
+// Resolves with the queue in dispatch order: per job, its estimated runtime
+// (estimated_secs) and completion time from queue start (eta_secs), both
+// null when there is no history to estimate from.
+ipcMain.handle('get-queue', (event, queueConfig) => {
+    const args = ['list', ...queueConfig.inputPaths, '--json'];
+    if (queueConfig.program) args.push('--program', queueConfig.program);
+    if (queueConfig.database) args.push('--db', queueConfig.database);
+    if (queueConfig.policy) args.push('--policy', queueConfig.policy);
+    if (queueConfig.owner) args.push('--owner', queueConfig.owner);
+
+    return new Promise((resolve, reject) => {
+        const rustProcess = spawn(schedulerBinaryPath(), args);
+        let stdout = '';
+        let stderr = '';
+
+        rustProcess.stdout.on('data', (data) => { stdout += data.toString(); });
+        rustProcess.stderr.on('data', (data) => { stderr += data.toString(); });
+
+        rustProcess.on('error', (err) => {
+            reject(new Error(`Failed to execute scheduler: ${err.message}`));
+        });
+
+        rustProcess.on('close', (code) => {
+            if (code !== 0) {
+                reject(new Error(stderr || `Scheduler exited with code ${code}`));
+                return;
+            }
+            try {
+                resolve(JSON.parse(stdout));
+            } catch (err) {
+                reject(new Error(`Invalid queue output: ${err.message}`));
+            }
+        });
+    });
+});
@@ -30,6 +30,26 @@ contextBridge.exposeInMainWorld('electronAPI', {
         ipcRenderer.send('run-blast', jobConfig.inputPath);
     },
 
+    // 2b. Start a BLAST job from GenBank/RefSeq accessions instead of a file
+    startAccessionJob: (accessions) => {
+        ipcRenderer.send('run-accessions', accessions);
+    },
+
+    // 3. Fetch hit map data (per query: hits, HSP coordinates, strand, identity, bitscore)
+    getVisualization: (resultPath) => {
+        return ipcRenderer.invoke('get-visualization', resultPath);
+    },
+
+    // 4. Save a result file via a save dialog; resolves with the saved path, or null if cancelled
+    downloadResult: (resultPath) => {
+        return ipcRenderer.invoke('download-result', resultPath);
+    },
+
+    // 5. Preview dispatch order and ETAs: { inputPaths, program?, database?, policy?: 'fifo'|'sjf'|'fair', owner? }
+    getQueue: (queueConfig) => {
+        return ipcRenderer.invoke('get-queue', queueConfig);
+    },
+
     // =======================================================
     // Functions for the Renderer to receive messages from Main
     // =======================================================
@@ -39,6 +59,11 @@ contextBridge.exposeInMainWorld('electronAPI', {
         ipcRenderer.on('blast-job-output', (event, output) => callback(output));
     },
 
+    // Listens for result streaming progress: { job_id, bytes, records, finished }
+    onProgress: (callback) => {
+        ipcRenderer.on('blast-job-progress', (event, progress) => callback(progress));
+    },
+
     // Listens for errors (both spawn errors and dialog errors)
     onError: (callback) => {
         ipcRenderer.on('blast-job-error', (event, error) => callback(error));
@@ -52,6 +77,7 @@ contextBridge.exposeInMainWorld('electronAPI', {
     // Remove listeners (cleanup)
     removeAllListeners: () => {
         ipcRenderer.removeAllListeners('blast-job-output');
+        ipcRenderer.removeAllListeners('blast-job-progress');
         ipcRenderer.removeAllListeners('blast-job-error');
         ipcRenderer.removeAllListeners('blast-job-status');
     }